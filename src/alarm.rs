@@ -0,0 +1,433 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Alarms that ring at a time of day, on some days of the week.
+
+use crate::audio::{self, AudioSink, Sound};
+use crate::theme::{parse_pair, strip_comment};
+use crate::ClockTime;
+use std::path::{Path, PathBuf};
+
+const DAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Days of the week, a bit for each from Sunday.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Days(u8);
+
+impl Days {
+    pub const EVERY_DAY: Days = Days(0b111_1111);
+    pub const WEEKDAYS: Days = Days(0b011_1110);
+    pub const WEEKENDS: Days = Days(0b100_0001);
+
+    /// Parses `daily`, `weekdays`, `weekends`, or a comma separated list of days and ranges of
+    /// days, e.g. `mon-fri` or `mon,wed,fri`.
+    pub fn parse(s: &str) -> Option<Days> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "daily" => return Some(Days::EVERY_DAY),
+            "weekdays" => return Some(Days::WEEKDAYS),
+            "weekends" => return Some(Days::WEEKENDS),
+            _ => {}
+        }
+        let day = |name: &str| {
+            let name = name.trim().to_ascii_lowercase();
+            DAY_NAMES.iter().position(|day| *day == name)
+        };
+        let mut days = 0;
+        for part in s.split(',') {
+            match part.split_once('-') {
+                Some((first, last)) => {
+                    let (first, last) = (day(first)?, day(last)?);
+                    /* a range can wrap around the end of the week, e.g. fri-mon */
+                    let mut day = first;
+                    loop {
+                        days |= 1 << day;
+                        if day == last {
+                            break;
+                        }
+                        day = (day + 1) % 7;
+                    }
+                }
+                None => days |= 1 << day(part)?,
+            }
+        }
+        Some(Days(days))
+    }
+
+    /// Whether `wday`, from 0 for Sunday, is one of the days.
+    pub fn contains(self, wday: u8) -> bool {
+        self.0 & (1 << (wday % 7)) != 0
+    }
+}
+
+/// An alarm at `hour:minutes` local time on `days`, optionally playing a WAV file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alarm {
+    pub hour: u8,
+    pub minutes: u8,
+    pub days: Days,
+    pub sound: Option<PathBuf>,
+}
+
+impl Alarm {
+    /// Parses `HH:MM` for every day, or `HH:MM@DAYS` with days as in [`Days::parse`], e.g.
+    /// `07:30@weekdays`.
+    pub fn parse(s: &str) -> Result<Alarm, String> {
+        let (time, days) = match s.split_once('@') {
+            Some((time, days)) => (
+                time,
+                Days::parse(days).ok_or_else(|| {
+                    format!(
+                        "`{}` are not days of the week, e.g. weekdays or mon,wed,fri",
+                        days
+                    )
+                })?,
+            ),
+            None => (s, Days::EVERY_DAY),
+        };
+        let (hour, minutes) = time
+            .split_once(':')
+            .and_then(|(hour, minutes)| {
+                let hour = hour.trim().parse::<u8>().ok().filter(|h| *h < 24)?;
+                let minutes = minutes.trim().parse::<u8>().ok().filter(|m| *m < 60)?;
+                Some((hour, minutes))
+            })
+            .ok_or_else(|| format!("`{}` is not a time of day in the format HH:MM", time))?;
+        Ok(Alarm {
+            hour,
+            minutes,
+            days,
+            sound: None,
+        })
+    }
+
+    /// Parses an alarms file, in the same subset of TOML as theme files, with an `[[alarm]]`
+    /// table for each alarm:
+    ///
+    /// ```toml
+    /// [[alarm]]
+    /// time = "07:30"
+    /// days = "weekdays"  # optional, daily by default
+    /// sound = "rooster.wav"  # optional
+    /// ```
+    pub fn parse_file(input: &str) -> Result<Vec<Alarm>, String> {
+        /* the line each table starts at, with its keys */
+        let mut tables = vec![];
+        for (i, line) in input.lines().enumerate() {
+            let line_no = i + 1;
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line == "[[alarm]]" {
+                tables.push((line_no, vec![]));
+                continue;
+            }
+            let (key, value) = parse_pair(line_no, line)?;
+            tables
+                .last_mut()
+                .ok_or_else(|| format!("line {}: expected `[[alarm]]`", line_no))?
+                .1
+                .push((line_no, key, value));
+        }
+        tables
+            .into_iter()
+            .map(|(line_no, pairs)| {
+                let (mut time, mut days, mut sound) = (None, None, None);
+                for (line_no, key, value) in pairs {
+                    match key {
+                        "time" => {
+                            time = Some(
+                                Alarm::parse(value)
+                                    .map_err(|err| format!("line {}: {}", line_no, err))?,
+                            );
+                        }
+                        "days" => {
+                            days = Some(Days::parse(value).ok_or_else(|| {
+                                format!("line {}: `{}` are not days of the week", line_no, value)
+                            })?);
+                        }
+                        "sound" => sound = Some(PathBuf::from(value)),
+                        _ => return Err(format!("line {}: unknown key `{}`", line_no, key)),
+                    }
+                }
+                let alarm: Alarm =
+                    time.ok_or_else(|| format!("line {}: alarm has no `time`", line_no))?;
+                Ok(Alarm {
+                    days: days.unwrap_or(alarm.days),
+                    sound,
+                    ..alarm
+                })
+            })
+            .collect()
+    }
+
+    /// Reads an alarms file, see [`Alarm::parse_file`]. Sounds are relative to the file.
+    pub fn load(path: &Path) -> Result<Vec<Alarm>, String> {
+        let input =
+            std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let mut alarms =
+            Alarm::parse_file(&input).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        for alarm in &mut alarms {
+            alarm.sound = alarm.sound.take().map(|sound| dir.join(sound));
+        }
+        Ok(alarms)
+    }
+
+    /// Whether the alarm rings in the minute of `time`.
+    pub fn is_due(&self, time: &ClockTime) -> bool {
+        self.days.contains(time.wday) && (self.hour, self.minutes) == (time.hour, time.minutes)
+    }
+}
+
+/* Seconds between the starts of the sound of a ringing alarm, at the least. */
+const REPLAY_INTERVAL: i64 = 1;
+
+/// Rings [`Alarm`]s as a clock reaches them, playing their sounds on an [`AudioSink`] over
+/// and over until dismissed.
+pub struct AlarmClock<S: AudioSink = audio::Silence> {
+    alarms: Vec<(Alarm, Option<Sound>)>,
+    sink: S,
+    /* the minute checked last, so that an alarm rings once */
+    checked: Option<(u8, u8, u8)>,
+    ringing: Option<usize>,
+    /* whether the sound of the ringing alarm still plays */
+    playing: bool,
+    /* when its sound was last started */
+    played: Option<i64>,
+}
+
+impl<S: AudioSink> AlarmClock<S> {
+    /// Loads the sounds of `alarms`, to play on `sink`.
+    pub fn new(alarms: Vec<Alarm>, sink: S) -> Result<Self, String> {
+        let alarms = alarms
+            .into_iter()
+            .map(|alarm| {
+                let sound = alarm.sound.as_deref().map(Sound::load).transpose()?;
+                Ok((alarm, sound))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(AlarmClock {
+            alarms,
+            sink,
+            checked: None,
+            ringing: None,
+            playing: false,
+            played: None,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.alarms.is_empty()
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    /// The alarm that is ringing.
+    pub fn ringing(&self) -> Option<&Alarm> {
+        self.ringing.map(|i| &self.alarms[i].0)
+    }
+
+    /// Rings the first alarm due at `time`, if the clock just reached its minute, and plays its
+    /// sound again if it is over, at most once a second. Returns whether an alarm is ringing.
+    ///
+    /// A sound that could not be played, or whose player stopped before its end, is an error
+    /// once, and the alarm rings on silently.
+    pub fn update(&mut self, time: &ClockTime) -> Result<bool, String> {
+        let minute = (time.mday, time.hour, time.minutes);
+        if self.checked != Some(minute) {
+            self.checked = Some(minute);
+            if let Some(i) = self.alarms.iter().position(|(alarm, _)| alarm.is_due(time)) {
+                self.sink.stop();
+                self.ringing = Some(i);
+                self.playing = self.alarms[i].1.is_some();
+                self.played = None;
+            }
+        }
+        let Some(i) = self.ringing else {
+            return Ok(false);
+        };
+        if let (true, Some(sound)) = (self.playing, &self.alarms[i].1) {
+            let due = self
+                .played
+                .is_none_or(|played| time.timestamp - played >= REPLAY_INTERVAL);
+            let started = match self.sink.is_playing() {
+                Ok(false) if due => self.sink.play(sound).map(|()| true),
+                other => other.map(|_| false),
+            };
+            match started {
+                Ok(true) => self.played = Some(time.timestamp),
+                Ok(false) => {}
+                Err(err) => {
+                    self.playing = false;
+                    return Err(format!("{}: {}", sound.path.display(), err));
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Stops the ringing alarm, until the next one is due.
+    pub fn dismiss(&mut self) {
+        if self.ringing.take().is_some() {
+            self.sink.stop();
+        }
+        self.playing = false;
+        self.played = None;
+    }
+}
+
+#[test]
+fn test_alarm() {
+    assert_eq!(
+        Alarm::parse("07:30"),
+        Ok(Alarm {
+            hour: 7,
+            minutes: 30,
+            days: Days::EVERY_DAY,
+            sound: None,
+        })
+    );
+    assert_eq!(Alarm::parse("7:30@mon-fri").unwrap().days, Days::WEEKDAYS);
+    assert_eq!(Alarm::parse("07:30@Sat,sun").unwrap().days, Days::WEEKENDS);
+    assert_eq!(Days::parse("fri-mon"), Days::parse("fri,sat,sun,mon"));
+    assert!(Alarm::parse("24:00").is_err());
+    assert!(Alarm::parse("07:30@someday").is_err());
+
+    let alarms = Alarm::parse_file(
+        r#"
+# weekday mornings
+[[alarm]]
+time = "07:30"
+days = "weekdays"
+sound = "meow.wav"
+
+[[alarm]]
+time = "10:00"
+"#,
+    )
+    .unwrap();
+    assert_eq!(alarms.len(), 2);
+    assert_eq!(alarms[0].days, Days::WEEKDAYS);
+    assert_eq!(alarms[0].sound.as_deref(), Some(Path::new("meow.wav")));
+    assert_eq!(alarms[1], Alarm::parse("10:00").unwrap());
+    assert!(Alarm::parse_file("time = \"07:30\"").is_err());
+    assert!(Alarm::parse_file("[[alarm]]\ndays = \"daily\"").is_err());
+    assert!(Alarm::parse_file("[[alarm]]\ntime = \"07:30\"\nsnooze = \"5\"").is_err());
+
+    /* 2021-11-22 was a Monday */
+    let sound = Sound::parse(Path::new("meow.wav"), audio::test_wav(&[0, 1000])).unwrap();
+    let mut clock = AlarmClock {
+        alarms: vec![(alarms[0].clone(), Some(sound.clone()))],
+        ..AlarmClock::new(vec![], audio::WavWriter(vec![])).unwrap()
+    };
+    let monday = ClockTime {
+        hour: 7,
+        minutes: 29,
+        seconds: 59,
+        nanos: 0,
+        mday: 22,
+        wday: 1,
+        timestamp: 1637559000 - 1,
+    };
+    assert_eq!(clock.update(&monday), Ok(false));
+    let due = ClockTime {
+        minutes: 30,
+        seconds: 0,
+        ..monday
+    };
+    assert_eq!(clock.update(&due), Ok(true));
+    assert_eq!(clock.ringing(), Some(&alarms[0]));
+    /* the sound plays again whenever it is over, until dismissed */
+    let next_second = ClockTime {
+        seconds: 1,
+        timestamp: due.timestamp + 1,
+        ..due
+    };
+    assert_eq!(clock.update(&next_second), Ok(true));
+    assert_eq!(
+        clock.sink().0,
+        [sound.bytes.clone(), sound.bytes.clone()].concat()
+    );
+    clock.dismiss();
+    assert_eq!(clock.update(&ClockTime { seconds: 2, ..due }), Ok(false));
+
+    /* not on Saturdays */
+    let saturday = ClockTime {
+        mday: 27,
+        wday: 6,
+        ..due
+    };
+    assert_eq!(clock.update(&saturday), Ok(false));
+
+    /* a sound that is over at once is not started again every frame */
+    #[derive(Default)]
+    struct Blip {
+        plays: usize,
+        fails: bool,
+    }
+    impl AudioSink for Blip {
+        fn play(&mut self, _: &Sound) -> std::io::Result<()> {
+            self.plays += 1;
+            Ok(())
+        }
+
+        fn is_playing(&mut self) -> std::io::Result<bool> {
+            match self.fails && self.plays > 0 {
+                true => Err(std::io::Error::other("no sound device")),
+                false => Ok(false),
+            }
+        }
+    }
+    let mut clock = AlarmClock {
+        alarms: vec![(alarms[0].clone(), Some(sound))],
+        ..AlarmClock::new(vec![], Blip::default()).unwrap()
+    };
+    for frame in 0..20 {
+        let time = ClockTime {
+            nanos: frame % 10 * 100_000_000,
+            seconds: frame as u64 / 10,
+            timestamp: due.timestamp + frame as i64 / 10,
+            ..due
+        };
+        assert_eq!(clock.update(&time), Ok(true));
+    }
+    assert_eq!(clock.sink().plays, 2);
+    /* and a player that fails is an error once */
+    clock.dismiss();
+    clock.sink = Blip {
+        plays: 0,
+        fails: true,
+    };
+    clock.checked = None;
+    assert_eq!(clock.update(&due), Ok(true));
+    assert!(clock.update(&next_second).is_err());
+    assert_eq!(
+        clock.update(&ClockTime {
+            seconds: 2,
+            ..next_second
+        }),
+        Ok(true)
+    );
+    assert_eq!(clock.sink().plays, 1);
+}
@@ -0,0 +1,259 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Alarm sounds: WAV files, and where to play them.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// A PCM WAV file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sound {
+    pub path: PathBuf,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub bits_per_sample: u16,
+    /// How long it plays.
+    pub duration: Duration,
+    /// The whole file.
+    pub bytes: Vec<u8>,
+}
+
+impl Sound {
+    /// Checks that `bytes` are a PCM WAV file, which every player can play.
+    pub fn parse(path: &Path, bytes: Vec<u8>) -> Result<Sound, String> {
+        if bytes.len() < 12 || &bytes[..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err("not a WAV file".to_string());
+        }
+        let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+        let u32_at =
+            |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        let mut format = None;
+        let mut pos = 12;
+        while pos + 8 <= bytes.len() {
+            let (id, len) = (&bytes[pos..pos + 4], u32_at(pos + 4) as usize);
+            let body = pos + 8;
+            if body + len > bytes.len() {
+                return Err(format!("truncated `{}` chunk", String::from_utf8_lossy(id)));
+            }
+            match id {
+                b"fmt " if len >= 16 => {
+                    /* 1 is integer PCM, 3 is IEEE float */
+                    if !matches!(u16_at(body), 1 | 3) {
+                        return Err(format!("unsupported WAV encoding {}", u16_at(body)));
+                    }
+                    format = Some((u16_at(body + 2), u32_at(body + 4), u16_at(body + 14)));
+                }
+                b"data" => {
+                    let (channels, sample_rate, bits_per_sample) =
+                        format.ok_or("`data` chunk before `fmt ` chunk")?;
+                    let bytes_per_second =
+                        sample_rate as u64 * channels as u64 * bits_per_sample.div_ceil(8) as u64;
+                    if bytes_per_second == 0 {
+                        return Err("no samples per second".to_string());
+                    }
+                    return Ok(Sound {
+                        path: path.to_path_buf(),
+                        channels,
+                        sample_rate,
+                        bits_per_sample,
+                        duration: Duration::from_nanos(
+                            len as u64 * 1_000_000_000 / bytes_per_second,
+                        ),
+                        bytes,
+                    });
+                }
+                _ => {}
+            }
+            /* chunks are padded to an even length */
+            pos = body + len + len % 2;
+        }
+        Err("no `data` chunk".to_string())
+    }
+
+    pub fn load(path: &Path) -> Result<Sound, String> {
+        let bytes = std::fs::read(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        Sound::parse(path, bytes).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+}
+
+/// Where alarm sounds are played.
+pub trait AudioSink {
+    /// Starts playing `sound`, stopping any sound still playing.
+    fn play(&mut self, sound: &Sound) -> io::Result<()>;
+
+    /// Whether the last sound is still playing, or an error if it stopped without being played
+    /// to its end.
+    fn is_playing(&mut self) -> io::Result<bool> {
+        Ok(false)
+    }
+
+    /// Stops the sound, if it is still playing.
+    fn stop(&mut self) {}
+}
+
+/// Plays nothing.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Silence;
+
+impl AudioSink for Silence {
+    fn play(&mut self, _: &Sound) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes every sound it plays to a writer, e.g. a file, instead of playing it.
+#[derive(Debug, Default)]
+pub struct WavWriter<W: Write>(pub W);
+
+impl<W: Write> AudioSink for WavWriter<W> {
+    fn play(&mut self, sound: &Sound) -> io::Result<()> {
+        self.0.write_all(&sound.bytes)
+    }
+}
+
+/// Plays sounds with the player program of the system: `afplay` on macOS, PowerShell on
+/// Windows and `aplay` elsewhere.
+#[derive(Debug, Default)]
+pub struct Player {
+    child: Option<Child>,
+    /* when the sound playing is over, at the earliest */
+    ends: Option<Instant>,
+}
+
+impl Player {
+    fn command(path: &Path) -> Command {
+        let mut command;
+        if cfg!(target_os = "macos") {
+            command = Command::new("afplay");
+            command.arg(path);
+        } else if cfg!(target_os = "windows") {
+            command = Command::new("powershell");
+            command.arg("-c").arg(format!(
+                "(New-Object Media.SoundPlayer '{}').PlaySync()",
+                path.display().to_string().replace('\'', "''")
+            ));
+        } else {
+            command = Command::new("aplay");
+            command.arg("-q").arg(path);
+        }
+        command
+    }
+}
+
+impl AudioSink for Player {
+    fn play(&mut self, sound: &Sound) -> io::Result<()> {
+        self.stop();
+        self.child = Some(
+            Player::command(&sound.path)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()?,
+        );
+        self.ends = Some(Instant::now() + sound.duration);
+        Ok(())
+    }
+
+    fn is_playing(&mut self) -> io::Result<bool> {
+        let Some(child) = &mut self.child else {
+            return Ok(false);
+        };
+        let Some(status) = child.try_wait()? else {
+            return Ok(true);
+        };
+        self.child = None;
+        /* e.g. no sound device, or a format the player does not know */
+        if !status.success() {
+            return Err(io::Error::other(format!("player failed with {}", status)));
+        }
+        if self.ends.take().is_some_and(|ends| Instant::now() < ends) {
+            return Err(io::Error::other(
+                "player stopped before the end of the sound",
+            ));
+        }
+        Ok(false)
+    }
+
+    fn stop(&mut self) {
+        self.ends = None;
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn test_wav(samples: &[i16]) -> Vec<u8> {
+    let mut wav = vec![];
+    let data_len = 2 * samples.len() as u32;
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    /* PCM, mono, 8000 Hz, 16000 bytes per second, 2 bytes per frame, 16 bits */
+    for field in [1u16, 1] {
+        wav.extend_from_slice(&field.to_le_bytes());
+    }
+    wav.extend_from_slice(&8000u32.to_le_bytes());
+    wav.extend_from_slice(&16000u32.to_le_bytes());
+    for field in [2u16, 16] {
+        wav.extend_from_slice(&field.to_le_bytes());
+    }
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+#[test]
+fn test_sound() {
+    let path = Path::new("meow.wav");
+    let wav = test_wav(&[0, 1000, -1000, 0]);
+    let sound = Sound::parse(path, wav.clone()).unwrap();
+    assert_eq!(
+        (sound.channels, sound.sample_rate, sound.bits_per_sample),
+        (1, 8000, 16)
+    );
+
+    let mut sink = WavWriter(vec![]);
+    sink.play(&sound).unwrap();
+    assert_eq!(sink.0, wav);
+    assert!(!sink.is_playing().unwrap());
+    assert_eq!(sound.duration, Duration::from_micros(500));
+
+    assert!(Sound::parse(path, b"RIFF\0\0\0\0AVI LIST".to_vec()).is_err());
+    assert!(Sound::parse(path, wav[..wav.len() - 1].to_vec()).is_err());
+    let mut compressed = wav;
+    compressed[20] = 2;
+    assert!(Sound::parse(path, compressed).is_err());
+}
@@ -0,0 +1,134 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Idle animations of the eyes: blinks at random intervals, now and then two in a row, and
+//! sleepy lids late at night.
+
+use crate::{ClockTime, Lids};
+
+/* The lids of a blink frame by frame, and of a double blink, `Open` being the lids at rest. */
+const BLINK: [Lids; 3] = [Lids::Half, Lids::Closed, Lids::Half];
+const DOUBLE_BLINK: [Lids; 7] = [
+    Lids::Half,
+    Lids::Closed,
+    Lids::Half,
+    Lids::Open,
+    Lids::Half,
+    Lids::Closed,
+    Lids::Half,
+];
+
+/* Frames between blinks, 3 to 8 seconds at 10 frames a second. */
+const MIN_WAIT: u64 = 30;
+const MAX_WAIT: u64 = 80;
+
+/* One blink in DOUBLE_BLINK_ODDS is a double blink. */
+const DOUBLE_BLINK_ODDS: u64 = 5;
+
+/// Whether it is late at night, from 23:00 to 05:00, when the cat gets sleepy.
+pub fn is_late(time: &ClockTime) -> bool {
+    time.hour >= 23 || time.hour < 5
+}
+
+/// Decides the lids of every frame: open, with a blink every few seconds.
+pub struct Blinker {
+    /* xorshift state, never zero */
+    state: u64,
+    /* frames until the next blink */
+    wait: u64,
+    /* the lids of the blink going on, and its next frame */
+    blink: &'static [Lids],
+    step: usize,
+}
+
+impl Blinker {
+    /// Blinks at random intervals drawn from `seed`, e.g. the time: the same seed blinks the same.
+    pub fn new(seed: u64) -> Self {
+        let mut blinker = Blinker {
+            state: (seed ^ 0x9e37_79b9_7f4a_7c15).max(1),
+            wait: 0,
+            blink: &[],
+            step: 0,
+        };
+        blinker.wait = blinker.random() % MAX_WAIT;
+        blinker
+    }
+
+    fn random(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// The lids of the next frame, which are half closed between blinks if `sleepy`.
+    pub fn next(&mut self, sleepy: bool) -> Lids {
+        let rest = if sleepy { Lids::Half } else { Lids::Open };
+        if let Some(lids) = self.blink.get(self.step) {
+            self.step += 1;
+            return if *lids == Lids::Open { rest } else { *lids };
+        }
+        if self.wait > 0 {
+            self.wait -= 1;
+            return rest;
+        }
+        self.blink = if self.random().is_multiple_of(DOUBLE_BLINK_ODDS) {
+            &DOUBLE_BLINK
+        } else {
+            &BLINK
+        };
+        self.step = 0;
+        self.wait = MIN_WAIT + self.random() % (MAX_WAIT - MIN_WAIT + 1);
+        self.next(sleepy)
+    }
+}
+
+#[test]
+fn test_blinker() {
+    let lids = |seed, sleepy| {
+        let mut blinker = Blinker::new(seed);
+        (0..2000)
+            .map(|_| blinker.next(sleepy))
+            .collect::<Vec<Lids>>()
+    };
+    let awake = lids(1637831210, false);
+    assert_eq!(awake, lids(1637831210, false));
+    assert_ne!(awake, lids(1637831211, false));
+
+    let closed = (0..awake.len())
+        .filter(|i| awake[*i] == Lids::Closed)
+        .collect::<Vec<usize>>();
+    /* 2000 frames are 200 seconds */
+    assert!((200 / 8..=200 / 3 * 2).contains(&closed.len()));
+    /* blinks are apart, but for double blinks */
+    assert!(closed
+        .windows(2)
+        .all(|w| w[1] - w[0] == 4 || w[1] - w[0] > MIN_WAIT as usize));
+    assert!(closed.windows(2).any(|w| w[1] - w[0] == 4));
+    assert!(closed
+        .iter()
+        .all(|i| awake[i - 1] == Lids::Half && awake[i + 1] == Lids::Half));
+
+    /* a sleepy cat never opens its eyes all the way */
+    let sleepy = lids(1637831210, true);
+    assert!(!sleepy.contains(&Lids::Open));
+    assert!(sleepy.contains(&Lids::Closed));
+}
@@ -1,5 +1,5 @@
-const CAT_WIDTH: usize = 150;
-const CAT_HEIGHT: usize = 300;
+pub const CAT_WIDTH: usize = 150;
+pub const CAT_HEIGHT: usize = 300;
 const CAT_BITS: &[u8] = &[
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
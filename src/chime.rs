@@ -0,0 +1,141 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Hourly chimes: like a cuckoo clock, the cat strikes the hour with its tail.
+
+use crate::ClockTime;
+
+/// A daily stretch of time when the clock keeps quiet, e.g. at night.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuietHours {
+    /// Hour and minutes it starts at.
+    pub from: (u8, u8),
+    /// Hour and minutes it ends at, which can be past midnight.
+    pub to: (u8, u8),
+}
+
+/* Parses HH:MM or HH into hour and minutes. */
+fn parse_hm(s: &str) -> Option<(u8, u8)> {
+    let (hour, minutes) = s.trim().split_once(':').unwrap_or((s.trim(), "0"));
+    let hour = hour.parse::<u8>().ok().filter(|h| *h < 24)?;
+    let minutes = minutes.parse::<u8>().ok().filter(|m| *m < 60)?;
+    Some((hour, minutes))
+}
+
+impl QuietHours {
+    /// Parses `FROM-TO` in `HH:MM` or `HH` format, e.g. `22:00-07:30` or `23-7`.
+    pub fn parse(s: &str) -> Option<QuietHours> {
+        let (from, to) = s.split_once('-')?;
+        Some(QuietHours {
+            from: parse_hm(from)?,
+            to: parse_hm(to)?,
+        })
+    }
+
+    pub fn contains(&self, hour: u8, minutes: u8) -> bool {
+        let time = (hour, minutes);
+        if self.from <= self.to {
+            self.from <= time && time < self.to
+        } else {
+            self.from <= time || time < self.to
+        }
+    }
+}
+
+/// Strikes the hours of a [`ClockTime`] that is updated every frame, once for each hour on the
+/// face.
+#[derive(Clone, Debug, Default)]
+pub struct Chime {
+    quiet: Option<QuietHours>,
+    /* the hour of the last update */
+    hour: Option<u8>,
+    /* strikes left of the hour being struck */
+    strikes: u32,
+}
+
+impl Chime {
+    pub fn new(quiet: Option<QuietHours>) -> Self {
+        Chime {
+            quiet,
+            hour: None,
+            strikes: 0,
+        }
+    }
+
+    /// Starts striking the hour if `time` is in a new hour outside the quiet hours, and returns
+    /// the number of strikes, from 1 to 12.
+    pub fn update(&mut self, time: &ClockTime) -> Option<u32> {
+        let previous = self.hour.replace(time.hour);
+        if previous.is_none_or(|hour| hour == time.hour)
+            || self.quiet.is_some_and(|quiet| quiet.contains(time.hour, 0))
+        {
+            return None;
+        }
+        self.strikes = (time.hour as u32 + 11) % 12 + 1;
+        Some(self.strikes)
+    }
+
+    /// Whether the hour is being struck.
+    pub fn is_striking(&self) -> bool {
+        self.strikes > 0
+    }
+
+    /// Strikes once, e.g. when the tail reaches a side.
+    pub fn strike(&mut self) {
+        self.strikes = self.strikes.saturating_sub(1);
+    }
+}
+
+#[test]
+fn test_chime() {
+    let night = QuietHours::parse("22:30-7").unwrap();
+    assert_eq!(night.to, (7, 0));
+    assert!(night.contains(23, 0) && night.contains(3, 0) && night.contains(22, 30));
+    assert!(!night.contains(7, 0) && !night.contains(12, 0) && !night.contains(22, 29));
+    assert!(QuietHours::parse("12:00-24:00").is_none());
+    assert!(QuietHours::parse("noon").is_none());
+
+    let time = ClockTime {
+        hour: 14,
+        minutes: 59,
+        seconds: 59,
+        nanos: 0,
+        mday: 25,
+        wday: 4,
+        timestamp: 1637845199,
+    };
+    let mut chime = Chime::new(Some(night));
+    assert_eq!(chime.update(&time), None);
+    let three = ClockTime {
+        hour: 15,
+        minutes: 0,
+        ..time
+    };
+    assert_eq!(chime.update(&three), Some(3));
+    assert_eq!(chime.update(&three), None);
+    for _ in 0..3 {
+        assert!(chime.is_striking());
+        chime.strike();
+    }
+    assert!(!chime.is_striking());
+    assert_eq!(chime.update(&ClockTime { hour: 0, ..time }), None);
+    assert_eq!(chime.update(&ClockTime { hour: 12, ..time }), Some(12));
+}
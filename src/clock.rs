@@ -0,0 +1,259 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crate::tz;
+use std::time::SystemTime;
+
+/// A broken down wall clock time, as displayed by the clock face.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClockTime {
+    pub hour: u8,
+    pub minutes: u8,
+    pub seconds: u64,
+    /// Nanoseconds past `seconds`, for hands that sweep instead of ticking.
+    pub nanos: u32,
+    /// Day of the month, shown with the date overlay.
+    pub mday: u8,
+    /// Day of the week, from 0 for Sunday to 6 for Saturday, for alarms.
+    pub wday: u8,
+    /// Seconds since the UNIX epoch, negative before 1970, used for the moon phase.
+    pub timestamp: i64,
+}
+
+impl ClockTime {
+    /// Local time in `zone` at `time` seconds since the UNIX epoch.
+    pub fn in_zone(time: i64, zone: &tz::TimeZone) -> Self {
+        Self::from_local(time + zone.offset(time), time)
+    }
+
+    /* The time on a clock showing local seconds since the UNIX epoch at time */
+    fn from_local(local: i64, time: i64) -> Self {
+        let seconds = local.rem_euclid(86400);
+        let days = local.div_euclid(86400);
+        let (_, _, mday) = tz::civil_from_days(days);
+        ClockTime {
+            hour: (seconds / 3600) as u8,
+            minutes: (seconds / 60 % 60) as u8,
+            seconds: (seconds % 60) as u64,
+            nanos: 0,
+            mday: mday as u8,
+            /* 1970-01-01 was a Thursday */
+            wday: (days + 4).rem_euclid(7) as u8,
+            timestamp: time,
+        }
+    }
+}
+
+/// A source of the current time, that tests can fake.
+pub trait WallClock {
+    /// Seconds since the UNIX epoch.
+    fn now(&self) -> i64;
+
+    /// Seconds since the UNIX epoch, and nanoseconds past them.
+    fn now_precise(&self) -> (i64, u32) {
+        (self.now(), 0)
+    }
+}
+
+/// The real time clock of the system.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl WallClock for SystemClock {
+    fn now(&self) -> i64 {
+        unsafe { libc::time(std::ptr::null_mut()) as i64 }
+    }
+
+    fn now_precise(&self) -> (i64, u32) {
+        match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(since) => (since.as_secs() as i64, since.subsec_nanos()),
+            /* a clock set before 1970 */
+            Err(_) => (self.now(), 0),
+        }
+    }
+}
+
+/// Which local time a clock shows.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Zone {
+    /// The local time of the system.
+    #[default]
+    Local,
+    /// A time zone of the tz database.
+    Tz(tz::TimeZone),
+}
+
+impl Zone {
+    /// Offset of local time from UTC at `time` seconds since the UNIX epoch, in seconds east of
+    /// Greenwich.
+    pub fn utc_offset(&self, time: i64) -> i64 {
+        match self {
+            Zone::Local => {
+                let tm = localtime(time as libc::time_t);
+                let days = tz::days_from_civil(
+                    tm.tm_year as i64 + 1900,
+                    tm.tm_mon as i64 + 1,
+                    tm.tm_mday as i64,
+                );
+                days * 86400 + tm.tm_hour as i64 * 3600 + tm.tm_min as i64 * 60 + tm.tm_sec as i64
+                    - time
+            }
+            Zone::Tz(zone) => zone.offset(time),
+        }
+    }
+
+    /// Local time at `time` seconds since the UNIX epoch.
+    pub fn time(&self, time: i64) -> ClockTime {
+        ClockTime::from_local(time + self.utc_offset(time), time)
+    }
+}
+
+/// Tells the time to show, reading it afresh from a [`WallClock`] every time so that it follows
+/// the clock across suspend and resume, clock changes and daylight saving time.
+pub struct Timekeeper<C: WallClock = SystemClock> {
+    clock: C,
+    zone: Zone,
+    /* seconds added to local time, only for the hands and date */
+    offset: i64,
+    /* seconds added to the wall clock */
+    shift: i64,
+}
+
+impl<C: WallClock> Timekeeper<C> {
+    pub fn new(clock: C, zone: Zone) -> Self {
+        Timekeeper {
+            clock,
+            zone,
+            offset: 0,
+            shift: 0,
+        }
+    }
+
+    /// Shows local time moved by `offset` seconds, while the sun and moon keep to the real
+    /// time.
+    pub fn set_offset(&mut self, offset: i64) {
+        self.offset = offset;
+    }
+
+    /// Moves time along so that it is `hour:minutes:seconds` now, and keeps going from there.
+    pub fn set_time_of_day(&mut self, hour: u8, minutes: u8, seconds: u8) {
+        let time = self.now();
+        let shown = time.hour as i64 * 3600 + time.minutes as i64 * 60 + time.seconds as i64;
+        let at = hour as i64 * 3600 + minutes as i64 * 60 + seconds as i64;
+        self.shift += at - shown;
+    }
+
+    /// The time to show now.
+    pub fn now(&self) -> ClockTime {
+        let (now, nanos) = self.clock.now_precise();
+        let timestamp = now + self.shift;
+        let local = timestamp + self.zone.utc_offset(timestamp) + self.offset;
+        ClockTime {
+            nanos,
+            ..ClockTime::from_local(local, timestamp)
+        }
+    }
+}
+
+pub fn localtime(time: libc::time_t) -> libc::tm {
+    let mut tm = std::mem::MaybeUninit::<libc::tm>::uninit();
+    unsafe {
+        #[cfg(target_os = "windows")]
+        libc::localtime_s(tm.as_mut_ptr(), &time as *const _);
+        #[cfg(not(target_os = "windows"))]
+        libc::localtime_r(&time as *const _, tm.as_mut_ptr());
+        tm.assume_init()
+    }
+}
+
+#[test]
+fn test_timekeeper() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[derive(Clone, Default)]
+    struct FakeClock(Rc<Cell<i64>>);
+    impl WallClock for FakeClock {
+        fn now(&self) -> i64 {
+            self.0.get()
+        }
+    }
+
+    let clock = FakeClock::default();
+    let athens = tz::TimeZone::from_posix("EET-2EEST,M3.5.0/3,M10.5.0/4").unwrap();
+    let mut timekeeper = Timekeeper::new(clock.clone(), Zone::Tz(athens));
+    let hms = |time: ClockTime| (time.mday, time.hour, time.minutes, time.seconds);
+
+    /* 2021-11-25 09:06:50 UTC */
+    clock.0.set(1637831210);
+    assert_eq!(hms(timekeeper.now()), (25, 11, 6, 50));
+    /* the machine sleeps for a day and three hours */
+    clock.0.set(1637831210 + 27 * 3600 + 15);
+    assert_eq!(hms(timekeeper.now()), (26, 14, 7, 5));
+    /* and the clock is stepped back */
+    clock.0.set(1637831210 - 61);
+    assert_eq!(hms(timekeeper.now()), (25, 11, 5, 49));
+    assert_eq!(timekeeper.now().timestamp, 1637831210 - 61);
+
+    /* daylight saving time starts at 2021-03-28 01:00 UTC, 03:00 EET */
+    clock.0.set(1616893199);
+    assert_eq!(hms(timekeeper.now()), (28, 2, 59, 59));
+    clock.0.set(1616893200);
+    assert_eq!(hms(timekeeper.now()), (28, 4, 0, 0));
+
+    /* --offset moves the hands but not the sun and moon */
+    timekeeper.set_offset(-(2 * 3600 + 30 * 60));
+    assert_eq!(hms(timekeeper.now()), (28, 1, 30, 0));
+    assert_eq!(timekeeper.now().timestamp, 1616893200);
+    timekeeper.set_offset(2 * 3600 + 32 * 60);
+    assert_eq!(hms(timekeeper.now()), (28, 6, 32, 0));
+    timekeeper.set_offset(24 * 3600 + 59 * 60);
+    assert_eq!(hms(timekeeper.now()), (29, 4, 59, 0));
+    timekeeper.set_offset(0);
+
+    /* --at starts from another time of day and keeps going */
+    timekeeper.set_time_of_day(10, 8, 42);
+    assert_eq!(hms(timekeeper.now()), (28, 10, 8, 42));
+    clock.0.set(1616893200 + 90);
+    assert_eq!(hms(timekeeper.now()), (28, 10, 10, 12));
+
+    /* the system's local time zone agrees with the C library */
+    for time in [0, 1616893200, 1637831210] {
+        let tm = localtime(time as libc::time_t);
+        let local = Zone::Local.time(time);
+        assert_eq!(
+            (
+                local.mday,
+                local.hour,
+                local.minutes,
+                local.seconds,
+                local.wday
+            ),
+            (
+                tm.tm_mday as u8,
+                tm.tm_hour as u8,
+                tm.tm_min as u8,
+                tm.tm_sec as u64,
+                tm.tm_wday as u8
+            )
+        );
+    }
+}
@@ -33,6 +33,129 @@ include!("letters/kitkat/6.xbm.rs");
 include!("letters/kitkat/7.xbm.rs");
 include!("letters/kitkat/8.xbm.rs");
 include!("letters/kitkat/9.xbm.rs");
+include!("letters/kitkat/A.xbm.rs");
+include!("letters/kitkat/B.xbm.rs");
+include!("letters/kitkat/C.xbm.rs");
+include!("letters/kitkat/D.xbm.rs");
+include!("letters/kitkat/E.xbm.rs");
+include!("letters/kitkat/F.xbm.rs");
+include!("letters/kitkat/G.xbm.rs");
+include!("letters/kitkat/H.xbm.rs");
+include!("letters/kitkat/I.xbm.rs");
+include!("letters/kitkat/J.xbm.rs");
+include!("letters/kitkat/K.xbm.rs");
+include!("letters/kitkat/L.xbm.rs");
+include!("letters/kitkat/M.xbm.rs");
+include!("letters/kitkat/N.xbm.rs");
+include!("letters/kitkat/O.xbm.rs");
+include!("letters/kitkat/P.xbm.rs");
+include!("letters/kitkat/Q.xbm.rs");
+include!("letters/kitkat/R.xbm.rs");
+include!("letters/kitkat/S.xbm.rs");
+include!("letters/kitkat/T.xbm.rs");
+include!("letters/kitkat/U.xbm.rs");
+include!("letters/kitkat/V.xbm.rs");
+include!("letters/kitkat/W.xbm.rs");
+include!("letters/kitkat/X.xbm.rs");
+include!("letters/kitkat/Y.xbm.rs");
+include!("letters/kitkat/Z.xbm.rs");
+
+/// The glyph of decimal digit `d`, as used for the date.
+pub fn digit(d: u8) -> Image {
+    let (bits, width, height) = match d {
+        0 => (_0_BITS, _0_WIDTH, _0_HEIGHT),
+        1 => (_1_BITS, _1_WIDTH, _1_HEIGHT),
+        2 => (_2_BITS, _2_WIDTH, _2_HEIGHT),
+        3 => (_3_BITS, _3_WIDTH, _3_HEIGHT),
+        4 => (_4_BITS, _4_WIDTH, _4_HEIGHT),
+        5 => (_5_BITS, _5_WIDTH, _5_HEIGHT),
+        6 => (_6_BITS, _6_WIDTH, _6_HEIGHT),
+        7 => (_7_BITS, _7_WIDTH, _7_HEIGHT),
+        8 => (_8_BITS, _8_WIDTH, _8_HEIGHT),
+        9 => (_9_BITS, _9_WIDTH, _9_HEIGHT),
+        _ => panic!("{} is not a digit", d),
+    };
+    Image {
+        bytes: bits_to_bytes(bits, width),
+        width,
+        height,
+        x_offset: 0,
+        y_offset: 0,
+    }
+}
+
+/// The glyph of capital letter `c`, in the style of the date digits.
+pub fn letter(c: char) -> Option<Image> {
+    let (bits, width, height) = match c {
+        'A' => (_A_BITS, _A_WIDTH, _A_HEIGHT),
+        'B' => (_B_BITS, _B_WIDTH, _B_HEIGHT),
+        'C' => (_C_BITS, _C_WIDTH, _C_HEIGHT),
+        'D' => (_D_BITS, _D_WIDTH, _D_HEIGHT),
+        'E' => (_E_BITS, _E_WIDTH, _E_HEIGHT),
+        'F' => (_F_BITS, _F_WIDTH, _F_HEIGHT),
+        'G' => (_G_BITS, _G_WIDTH, _G_HEIGHT),
+        'H' => (_H_BITS, _H_WIDTH, _H_HEIGHT),
+        'I' => (_I_BITS, _I_WIDTH, _I_HEIGHT),
+        'J' => (_J_BITS, _J_WIDTH, _J_HEIGHT),
+        'K' => (_K_BITS, _K_WIDTH, _K_HEIGHT),
+        'L' => (_L_BITS, _L_WIDTH, _L_HEIGHT),
+        'M' => (_M_BITS, _M_WIDTH, _M_HEIGHT),
+        'N' => (_N_BITS, _N_WIDTH, _N_HEIGHT),
+        'O' => (_O_BITS, _O_WIDTH, _O_HEIGHT),
+        'P' => (_P_BITS, _P_WIDTH, _P_HEIGHT),
+        'Q' => (_Q_BITS, _Q_WIDTH, _Q_HEIGHT),
+        'R' => (_R_BITS, _R_WIDTH, _R_HEIGHT),
+        'S' => (_S_BITS, _S_WIDTH, _S_HEIGHT),
+        'T' => (_T_BITS, _T_WIDTH, _T_HEIGHT),
+        'U' => (_U_BITS, _U_WIDTH, _U_HEIGHT),
+        'V' => (_V_BITS, _V_WIDTH, _V_HEIGHT),
+        'W' => (_W_BITS, _W_WIDTH, _W_HEIGHT),
+        'X' => (_X_BITS, _X_WIDTH, _X_HEIGHT),
+        'Y' => (_Y_BITS, _Y_WIDTH, _Y_HEIGHT),
+        'Z' => (_Z_BITS, _Z_WIDTH, _Z_HEIGHT),
+        _ => return None,
+    };
+    Some(Image {
+        bytes: bits_to_bytes(bits, width),
+        width,
+        height,
+        x_offset: 0,
+        y_offset: 0,
+    })
+}
+
+/// `text` written with [`digit`]s and [`letter`]s, with lower case letters capitalised, or
+/// `None` if it has any other characters than those and spaces.
+pub fn caption(text: &str) -> Option<Image> {
+    /* width of a space, glyphs are one pixel apart */
+    const SPACE: usize = 3;
+    let glyphs = text
+        .chars()
+        .map(|c| match c {
+            ' ' => Some(None),
+            '0'..='9' => Some(Some(digit(c as u8 - b'0'))),
+            c => letter(c.to_ascii_uppercase()).map(Some),
+        })
+        .collect::<Option<Vec<Option<Image>>>>()?;
+    let width = glyphs
+        .iter()
+        .map(|g| g.as_ref().map_or(SPACE, |g| g.width) + 1)
+        .sum::<usize>()
+        .saturating_sub(1);
+    let height = glyphs.iter().flatten().map(|g| g.height).max().unwrap_or(0);
+    let mut ret = Image::new(width, height, 0, 0);
+    let mut x = 0;
+    for glyph in glyphs {
+        match glyph {
+            Some(glyph) => {
+                ret.copy(&glyph, x, 0);
+                x += glyph.width + 1;
+            }
+            None => x += SPACE + 1,
+        }
+    }
+    Some(ret)
+}
 
 pub fn make_date(mday: i64) -> Image {
     let mut ret = Image::new(
@@ -167,7 +290,7 @@ pub fn make_date(mday: i64) -> Image {
         };
 
         if let Some(ref first_digit) = first_digit {
-            ret.copy(&first_digit, 1, 2);
+            ret.copy(first_digit, 1, 2);
             ret.copy(&second_digit, DATE_WIDTH / 2 + 1, 2);
         } else {
             ret.copy(&second_digit, DATE_WIDTH / 2, 2);
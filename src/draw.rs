@@ -57,7 +57,7 @@ pub fn plot_line_with_width(
 ) {
     let width2 = wd / 2.0;
     plot_line_width(buffer, point_a, point_b, 1.0);
-    for w in ((-1.0 * width2) as i64)..(width2 as i64) {
+    for w in ((-width2) as i64)..(width2 as i64) {
         plot_line_width(
             buffer,
             (point_a.0 + w, point_a.1),
@@ -105,7 +105,7 @@ pub fn plot_line_width(
             //eprintln!(" x step ");
             e2 += dy;
             y2 = y0;
-            while e2 < ((ed as f64 * wd) as i64) && (y1 != y2 || dx > dy) {
+            while e2 < ((ed * wd) as i64) && (y1 != y2 || dx > dy) {
                 y2 += sy;
                 plot(buffer, (x0, y2));
                 points.push((x0, y2));
@@ -122,7 +122,7 @@ pub fn plot_line_width(
             /* y step */
             //eprintln!(" y step ");
             e2 = dx - e2;
-            while e2 < ((ed as f64 * wd) as i64) && (x1 != x2 || dx < dy) {
+            while e2 < ((ed * wd) as i64) && (x1 != x2 || dx < dy) {
                 x2 += sx;
                 plot(buffer, (x2, y0));
                 points.push((x2, y0));
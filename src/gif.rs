@@ -0,0 +1,250 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/* Largest code of GIF's variable-length-code LZW */
+const MAX_CODE: u16 = 4095;
+const MAX_CODE_SIZE: u8 = 12;
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.acc |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push((self.acc & 0xff) as u8);
+            self.acc >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push((self.acc & 0xff) as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Compresses palette `indices` with GIF flavoured LZW.
+pub fn lzw_encode(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear: u16 = 1 << min_code_size;
+    let eoi: u16 = clear + 1;
+    let mut out = BitWriter {
+        bytes: vec![],
+        acc: 0,
+        bits: 0,
+    };
+    let mut dict: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut code_size = min_code_size + 1;
+    let mut next_code = eoi + 1;
+
+    out.write(clear, code_size);
+    let mut indices = indices.iter();
+    let mut prefix: u16 = match indices.next() {
+        Some(i) => *i as u16,
+        None => {
+            out.write(eoi, code_size);
+            return out.finish();
+        }
+    };
+    for &k in indices {
+        if let Some(&code) = dict.get(&(prefix, k)) {
+            prefix = code;
+            continue;
+        }
+        out.write(prefix, code_size);
+        if next_code > MAX_CODE {
+            /* Dictionary is full, start over. */
+            out.write(clear, code_size);
+            dict.clear();
+            code_size = min_code_size + 1;
+            next_code = eoi + 1;
+        } else {
+            dict.insert((prefix, k), next_code);
+            next_code += 1;
+            /* The decoder lags one code behind, so switch sizes one code later. */
+            if next_code > (1 << code_size) && code_size < MAX_CODE_SIZE {
+                code_size += 1;
+            }
+        }
+        prefix = k as u16;
+    }
+    out.write(prefix, code_size);
+    out.write(eoi, code_size);
+    out.finish()
+}
+
+/// Writes `frames` of `width x height` `0RGB` pixels as a looping animated GIF, showing each
+/// frame for `delay` hundredths of a second.
+///
+/// All frames share one global palette, so they must contain at most 256 distinct colours in
+/// total, which is always the case for the clock.
+pub fn write_gif<W: Write>(
+    mut w: W,
+    frames: &[Vec<u32>],
+    width: usize,
+    height: usize,
+    delay: u16,
+) -> io::Result<()> {
+    let mut palette: Vec<u32> = vec![];
+    /* the index of each colour in the palette, looked up only where the colour changes since
+     * the frames have long runs of one colour */
+    let mut index: HashMap<u32, u8> = HashMap::new();
+    let mut previous = None;
+    for pixel in frames.iter().flatten() {
+        if previous != Some(pixel) && !index.contains_key(pixel) {
+            if palette.len() == 256 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "more than 256 colours in GIF frames",
+                ));
+            }
+            index.insert(*pixel, palette.len() as u8);
+            palette.push(*pixel);
+        }
+        previous = Some(pixel);
+    }
+    /* The colour table size is 2^(n+1) entries. */
+    let mut table_bits: u8 = 1;
+    while (1 << table_bits) < palette.len() {
+        table_bits += 1;
+    }
+    palette.resize(1 << table_bits, 0);
+
+    w.write_all(b"GIF89a")?;
+    w.write_all(&(width as u16).to_le_bytes())?;
+    w.write_all(&(height as u16).to_le_bytes())?;
+    /* global colour table, 8 bits per primary, table size */
+    w.write_all(&[0xf0 | (table_bits - 1), 0, 0])?;
+    for color in &palette {
+        w.write_all(&color.to_be_bytes()[1..])?;
+    }
+    /* NETSCAPE2.0 application extension: loop forever */
+    w.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+
+    let min_code_size = table_bits.max(2);
+    for frame in frames {
+        debug_assert_eq!(frame.len(), width * height);
+        /* graphic control extension: no disposal, no transparency */
+        w.write_all(&[0x21, 0xf9, 0x04, 0x04])?;
+        w.write_all(&delay.to_le_bytes())?;
+        w.write_all(&[0x00, 0x00])?;
+        /* image descriptor covering the whole screen */
+        w.write_all(&[0x2c, 0, 0, 0, 0])?;
+        w.write_all(&(width as u16).to_le_bytes())?;
+        w.write_all(&(height as u16).to_le_bytes())?;
+        w.write_all(&[0x00])?;
+
+        let mut run = None;
+        let indices = frame
+            .iter()
+            .map(|p| match run {
+                Some((color, i)) if color == *p => i,
+                _ => {
+                    let i = index[p];
+                    run = Some((*p, i));
+                    i
+                }
+            })
+            .collect::<Vec<u8>>();
+        w.write_all(&[min_code_size])?;
+        for block in lzw_encode(&indices, min_code_size).chunks(255) {
+            w.write_all(&[block.len() as u8])?;
+            w.write_all(block)?;
+        }
+        w.write_all(&[0x00])?;
+    }
+    w.write_all(&[0x3b])?;
+    w.flush()
+}
+
+#[test]
+fn test_lzw() {
+    fn lzw_decode(bytes: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear: u16 = 1 << min_code_size;
+        let eoi = clear + 1;
+        let mut table: Vec<Vec<u8>> = vec![];
+        let mut code_size = min_code_size + 1;
+        let mut prev: Option<u16> = None;
+        let mut ret = vec![];
+        let (mut acc, mut bits, mut bytes) = (0_u32, 0_u8, bytes.iter());
+        loop {
+            while bits < code_size {
+                acc |= (*bytes.next().unwrap() as u32) << bits;
+                bits += 8;
+            }
+            let code = (acc & ((1 << code_size) - 1)) as u16;
+            acc >>= code_size;
+            bits -= code_size;
+            if code == clear {
+                table = (0..clear).map(|i| vec![i as u8]).collect();
+                table.push(vec![]);
+                table.push(vec![]);
+                code_size = min_code_size + 1;
+                prev = None;
+                continue;
+            } else if code == eoi {
+                return ret;
+            }
+            let entry = if (code as usize) < table.len() {
+                table[code as usize].clone()
+            } else {
+                let mut e = table[prev.unwrap() as usize].clone();
+                e.push(e[0]);
+                e
+            };
+            if let Some(prev) = prev {
+                let mut e = table[prev as usize].clone();
+                e.push(entry[0]);
+                table.push(e);
+                if table.len() == (1 << code_size) && code_size < MAX_CODE_SIZE {
+                    code_size += 1;
+                }
+            }
+            ret.extend_from_slice(&entry);
+            prev = Some(code);
+        }
+    }
+
+    let input = (0..20_000_u32)
+        .map(|i| ((i * 7) % 13 % 4 + (i / 1000) % 3) as u8)
+        .collect::<Vec<u8>>();
+    assert_eq!(lzw_decode(&lzw_encode(&input, 3), 3), input);
+    /* enough noise to fill up the dictionary several times */
+    let mut seed: u32 = 1;
+    let input = (0..100_000)
+        .map(|_| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 16) as u8 & 0x0f
+        })
+        .collect::<Vec<u8>>();
+    assert_eq!(lzw_decode(&lzw_encode(&input, 4), 4), input);
+    assert_eq!(lzw_decode(&lzw_encode(&[1], 2), 2), vec![1]);
+    assert_eq!(lzw_decode(&lzw_encode(&[], 2), 2), vec![]);
+}
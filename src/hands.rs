@@ -35,26 +35,101 @@ pub const FACE_WIDTH: usize = 80;
 pub const FACE_HEIGHT: usize = 80;
 pub const FACE_OFFSET_X: usize = CAT_WIDTH / 2 - FACE_WIDTH / 2 - 1;
 pub const FACE_OFFSET_Y: usize = CAT_HEIGHT / 2 - FACE_HEIGHT / 2;
+
+pub const SECOND_HAND_WIDTH: i64 = 19;
+pub const SECOND_HAND_HEIGHT: i64 = 0;
+
+pub const MINUTE_HAND_WIDTH: i64 = 20;
+pub const MINUTE_HAND_HEIGHT: i64 = 3;
+
+pub const HOUR_HAND_WIDTH: i64 = 16;
+pub const HOUR_HAND_HEIGHT: i64 = 3;
+
+/// Blank image covering the clock face, to draw hands on, `scale` times the size.
+pub fn face(scale: usize) -> Image {
+    Image::new(
+        FACE_WIDTH * scale,
+        FACE_HEIGHT * scale,
+        FACE_OFFSET_X * scale,
+        FACE_OFFSET_Y * scale,
+    )
+}
+
+/* Half axes of the ring around the face, just outside its white, and how thick it is. */
+const RING_WIDTH: f64 = 33.0;
+const RING_HEIGHT: f64 = 44.0;
+const RING_THICKNESS: f64 = 2.0;
+
+/// The part of a ring around the face that goes `fraction` (`0.0` to `1.0`) of the way round,
+/// clockwise from high noon, e.g. to show how much of a Pomodoro phase is done.
+pub fn progress_ring(fraction: f64) -> Image {
+    let (rx, ry) = (RING_WIDTH + RING_THICKNESS, RING_HEIGHT + RING_THICKNESS);
+    let center = (
+        (FACE_OFFSET_X + FACE_WIDTH / 2) as f64 + 0.5,
+        (FACE_OFFSET_Y + FACE_HEIGHT / 2) as f64 + 0.5,
+    );
+    let (x_offset, y_offset) = ((center.0 - rx) as usize, (center.1 - ry) as usize);
+    let mut ring = Image::new(
+        (2.0 * rx) as usize + 1,
+        (2.0 * ry) as usize + 1,
+        x_offset,
+        y_offset,
+    );
+    for y in 0..ring.height {
+        for x in 0..ring.width {
+            let dx = (x + x_offset) as f64 + 0.5 - center.0;
+            let dy = (y + y_offset) as f64 + 0.5 - center.1;
+            let outside = |rx: f64, ry: f64| (dx / rx).powi(2) + (dy / ry).powi(2) >= 1.0;
+            if outside(rx, ry) || !outside(RING_WIDTH, RING_HEIGHT) {
+                continue;
+            }
+            /* clockwise from high noon, going round the ring at an even pace */
+            let turn = (dx / RING_WIDTH).atan2(-dy / RING_HEIGHT) / (2.0 * PI);
+            if turn.rem_euclid(1.0) < fraction {
+                ring.plot(x as i64, y as i64);
+            }
+        }
+    }
+    ring
+}
+
 /*
- *  DrawHand - Draws a hand.
+ *  Draws the outline of a hand, with a thicker pen if the face is drawn at a larger scale.
+ *  The end points are relative to the center of the face.
+ */
+fn plot_outline(buffer: &mut Image, a: (f64, f64), b: (f64, f64), wd: f64, antialias: bool) {
+    let center_point = ((buffer.width / 2) as i64, (buffer.height / 2) as i64); //(5,50);
+    let pen = (buffer.width / FACE_WIDTH) as i64;
+    if antialias {
+        let (cx, cy) = (center_point.0 as f64, center_point.1 as f64);
+        buffer.plot_line_width_aa(
+            (cx + a.0, cy + a.1),
+            (cx + b.0, cy + b.1),
+            pen.max(1) as f64,
+        );
+        return;
+    }
+    let a = (center_point.0 + a.0 as i64, center_point.1 + a.1 as i64);
+    let b = (center_point.0 + b.0 as i64, center_point.1 + b.1 as i64);
+    if pen > 1 {
+        buffer.plot_line_pen(a, b, pen);
+    } else {
+        buffer.plot_line_width(a, b, wd);
+    }
+}
+
+/*
+ *  HandPoints - Computes the vertices of a hand.
  *
  *  length is the maximum length of the hand.
  *  width is the half-width of the hand.
  *  fractionOfACircle is a fraction between 0 and 1 (inclusive) indicating
  *  how far around the circle (clockwise) from high noon.
  *
+ *  The vertices are relative to the center of the face.
  */
-pub fn draw_hand(
-    buffer: &mut Image,
-    length: i64,
-    width: i64,
-    _offset: i64,
-    fraction_of_a_circle: f64,
-) -> (i64, i64) {
-    let width = width as f64;
-    let length = length as f64;
-    //let offset = offset as f64;
-
+#[allow(clippy::needless_late_init)]
+pub fn hand_points(length: f64, width: f64, fraction_of_a_circle: f64) -> [(f64, f64); 3] {
     let angle: f64;
     let cos_angle: f64;
     let sin_angle: f64;
@@ -84,26 +159,41 @@ pub fn draw_hand(
      *      /     \
      *    2 ------- 3
      */
-    wc = (width as f64) * cos_angle;
-    ws = (width as f64) * sin_angle;
-    let center_point = ((FACE_WIDTH / 2) as i64, (FACE_HEIGHT / 2) as i64); //(5,50);
+    wc = width * cos_angle;
+    ws = width * sin_angle;
 
-    let a = (
-        center_point.0 + (length * sin_angle) as i64,
-        center_point.1 - (length * cos_angle) as i64,
-    );
-    let b = (
-        center_point.0 - (ws + wc) as i64,
-        center_point.1 + (wc - ws) as i64,
-    );
-    let c = (
-        center_point.0 - (ws - wc) as i64,
-        center_point.1 + (wc + ws) as i64,
-    );
-    buffer.plot_line_width(a, b, 0.0); /* 1 ---- 2 */
-    buffer.plot_line_width(b, c, 0.0); /* 2 ----- 3 */
-    buffer.plot_line_width(c, a, 0.0); /* 3 ----- 1(4) */
+    [
+        (length * sin_angle, -(length * cos_angle)),
+        (-(ws + wc), wc - ws),
+        (-(ws - wc), wc + ws),
+    ]
+}
 
+/*
+ *  DrawHand - Draws a hand.
+ *
+ *  length is the maximum length of the hand.
+ *  width is the half-width of the hand.
+ *  fractionOfACircle is a fraction between 0 and 1 (inclusive) indicating
+ *  how far around the circle (clockwise) from high noon.
+ *  antialias draws smooth edges.
+ *
+ */
+pub fn draw_hand(
+    buffer: &mut Image,
+    length: i64,
+    width: i64,
+    _offset: i64,
+    fraction_of_a_circle: f64,
+    antialias: bool,
+) -> (i64, i64) {
+    let center_point = ((buffer.width / 2) as i64, (buffer.height / 2) as i64); //(5,50);
+    let [a, b, c] = hand_points(length as f64, width as f64, fraction_of_a_circle);
+    plot_outline(buffer, a, b, 0.0, antialias); /* 1 ---- 2 */
+    plot_outline(buffer, b, c, 0.0, antialias); /* 2 ----- 3 */
+    plot_outline(buffer, c, a, 0.0, antialias); /* 3 ----- 1(4) */
+
+    let [a, b, c] = [a, b, c].map(|(x, y)| (center_point.0 + x as i64, center_point.1 + y as i64));
     (
         (a.0 + b.0 + c.0) / 3 + center_point.0,
         (a.1 + b.1 + c.1) / 3 + center_point.1,
@@ -111,7 +201,7 @@ pub fn draw_hand(
 }
 
 /*
- *  DrawSecond - Draws the second hand (diamond).
+ *  SecondPoints - Computes the vertices of the second hand (diamond).
  *
  *  length is the maximum length of the hand.
  *  width is the half-width of the hand.
@@ -119,17 +209,15 @@ pub fn draw_hand(
  *  fractionOfACircle is a fraction between 0 and 1 (inclusive) indicating
  *  how far around the circle (clockwise) from high noon.
  *
+ *  The vertices are relative to the center of the face.
  */
-pub fn draw_second(
-    buffer: &mut Image,
-    length: i64,
-    width: i64,
-    offset: i64,
+#[allow(clippy::needless_late_init)]
+pub fn second_points(
+    length: f64,
+    width: f64,
+    offset: f64,
     fraction_of_a_circle: f64,
-) {
-    let width = width as f64;
-    let length = length as f64;
-    let offset = offset as f64;
+) -> [(f64, f64); 4] {
     let angle: f64;
     let cos_angle: f64;
     let sin_angle: f64;
@@ -179,42 +267,47 @@ pub fn draw_second(
     wc = width * cos_angle;
     ws = width * sin_angle;
 
-    //buffer.draw_outline();
-    let center_point = ((FACE_WIDTH / 2) as i64, (FACE_HEIGHT / 2) as i64); //(5,50);
-                                                                            /*1 ---- 2 */
-    {
-        let x0 = center_point.0 + (length * sin_angle) as i64;
-        let y0 = center_point.1 - (length * cos_angle) as i64;
-        let x1 = center_point.0 + (ms - wc) as i64;
-        let y1 = center_point.1 - (mc + ws) as i64;
-        buffer.plot_line_width((x0, y0), (x1, y1), 1.0);
-        //eprintln!("1-2: 0: {:?} 1: {:?}", (x0, y0), (x1, y1));
-    }
-    /* 2-----3 */
-    {
-        let x0 = center_point.0 + (ms - wc) as i64;
-        let y0 = center_point.1 - (mc + ws) as i64;
-        let x1 = center_point.0 + (offset * sin_angle) as i64;
-        let y1 = center_point.1 - (offset * cos_angle) as i64;
-        buffer.plot_line_width((x0, y0), (x1, y1), 1.0);
-        //eprintln!("2-3: 0: {:?} 1: {:?}", (x0, y0), (x1, y1));
-    }
-    /* 3-----4 */
-    {
-        let x0 = center_point.0 + (offset * sin_angle) as i64;
-        let y0 = center_point.1 - (offset * cos_angle) as i64;
-        let x1 = center_point.0 + (ms + wc) as i64;
-        let y1 = center_point.1 - (mc - ws) as i64;
-        buffer.plot_line_width((x0, y0), (x1, y1), 1.0);
-        //eprintln!("3-4: 0: {:?} 1: {:?}", (x0, y0), (x1, y1));
-    }
-    /* 4-----1 */
-    {
-        let x0 = center_point.0 + (ms + wc) as i64;
-        let y0 = center_point.1 - (mc - ws) as i64;
-        let x1 = center_point.0 + (length * sin_angle) as i64;
-        let y1 = center_point.1 - (length * cos_angle) as i64;
-        buffer.plot_line_width((x0, y0), (x1, y1), 1.0);
-        //eprintln!("4-1: 0: {:?} 1: {:?}", (x0, y0), (x1, y1));
+    [
+        (length * sin_angle, -(length * cos_angle)),
+        (ms - wc, -(mc + ws)),
+        (offset * sin_angle, -(offset * cos_angle)),
+        (ms + wc, -(mc - ws)),
+    ]
+}
+
+/*
+ *  DrawSecond - Draws the second hand (diamond).
+ *
+ *  length is the maximum length of the hand.
+ *  width is the half-width of the hand.
+ *  offset is direct distance from Center to tail end.
+ *  fractionOfACircle is a fraction between 0 and 1 (inclusive) indicating
+ *  how far around the circle (clockwise) from high noon.
+ *  antialias draws smooth edges.
+ *
+ */
+pub fn draw_second(
+    buffer: &mut Image,
+    length: i64,
+    width: i64,
+    offset: i64,
+    fraction_of_a_circle: f64,
+    antialias: bool,
+) {
+    let points = second_points(
+        length as f64,
+        width as f64,
+        offset as f64,
+        fraction_of_a_circle,
+    );
+    /* 1-----2, 2-----3, 3-----4, 4-----1 */
+    for i in 0..points.len() {
+        plot_outline(
+            buffer,
+            points[i],
+            points[(i + 1) % points.len()],
+            1.0,
+            antialias,
+        );
     }
 }
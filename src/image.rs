@@ -21,6 +21,25 @@
 
 use super::*;
 use std::collections::VecDeque;
+
+/* Number of coverage steps of anti-aliased pixels, besides none */
+const AA_LEVELS: u32 = 32;
+
+/// How much of an [`Image`] pixel is covered by the foreground, from `0.0` (`WHITE`) to `1.0`
+/// (`BLACK`).
+pub fn coverage(pixel: u32) -> f64 {
+    1.0 - (pixel & 0xff) as f64 / 255.0
+}
+
+/// Mixes `fg` over `bg` with opacity `alpha`.
+pub fn blend(bg: u32, fg: u32, alpha: f64) -> u32 {
+    let channel = |shift: u32| {
+        let (b, f) = (((bg >> shift) & 0xff) as f64, ((fg >> shift) & 0xff) as f64);
+        ((b + (f - b) * alpha).round() as u32) << shift
+    };
+    channel(16) | channel(8) | channel(0)
+}
+
 pub struct Image {
     pub bytes: Vec<u32>,
     pub width: usize,
@@ -51,13 +70,43 @@ impl Image {
             y_offset,
         }
     }
-    pub fn draw(&self, buffer: &mut Vec<u32>, fg: u32, bg: Option<u32>) {
+    pub fn draw(&self, buffer: &mut [u32], fg: u32, bg: Option<u32>) {
+        self.draw_scaled(buffer, CAT_WIDTH, 1, fg, bg);
+    }
+
+    /// Draws into a framebuffer with rows of `row_width` pixels, blowing up each pixel and the
+    /// offsets by an integer `scale`.
+    pub fn draw_scaled(
+        &self,
+        buffer: &mut [u32],
+        row_width: usize,
+        scale: usize,
+        fg: u32,
+        bg: Option<u32>,
+    ) {
         for y in 0..self.height {
             for x in 0..self.width {
-                if self.bytes[y * self.width + x] == BLACK {
-                    buffer[(self.y_offset + y) * CAT_WIDTH + self.x_offset + x] = fg;
+                let pixel = self.bytes[y * self.width + x];
+                let color = if pixel == BLACK {
+                    fg
+                } else if pixel != WHITE {
+                    /* partially covered, composite over bg or what is already there */
+                    let alpha = coverage(pixel);
+                    let (bx, by) = ((self.x_offset + x) * scale, (self.y_offset + y) * scale);
+                    for row in buffer[by * row_width..].chunks_mut(row_width).take(scale) {
+                        for p in row[bx..bx + scale].iter_mut() {
+                            *p = blend(bg.unwrap_or(*p), fg, alpha);
+                        }
+                    }
+                    continue;
                 } else if let Some(bg) = bg {
-                    buffer[(self.y_offset + y) * CAT_WIDTH + self.x_offset + x] = bg;
+                    bg
+                } else {
+                    continue;
+                };
+                let (bx, by) = ((self.x_offset + x) * scale, (self.y_offset + y) * scale);
+                for row in buffer[by * row_width..].chunks_mut(row_width).take(scale) {
+                    row[bx..bx + scale].fill(color);
                 }
             }
         }
@@ -86,6 +135,8 @@ impl Image {
                 if let Some(p) = other.get((x - x_offset) as i64, (y - y_offset) as i64) {
                     if p == BLACK {
                         self.plot(x as i64, y as i64);
+                    } else if p != WHITE {
+                        self.plot_aa(x as i64, y as i64, coverage(p));
                     }
                 }
             }
@@ -107,6 +158,21 @@ impl Image {
         self.bytes[y * self.width + x] = BLACK;
     }
 
+    /// Plots a pixel `coverage` (`0.0` to `1.0`) covered by the foreground. Pixels keep the
+    /// largest coverage plotted on them, and plotting outside the image is ignored.
+    pub fn plot_aa(&mut self, x: i64, y: i64, coverage: f64) {
+        if x < 0 || y < 0 || y >= (self.height as i64) || x >= (self.width as i64) {
+            return;
+        }
+        /* Coverage is kept to AA_LEVELS steps, so that exported GIFs fit in their palette. */
+        let level = (coverage.clamp(0.0, 1.0) * AA_LEVELS as f64).round() as u32;
+        let value = 255 - level * 255 / AA_LEVELS;
+        let pixel = &mut self.bytes[y as usize * self.width + x as usize];
+        if value < (*pixel & 0xff) {
+            *pixel = (value << 16) | (value << 8) | value;
+        }
+    }
+
     pub fn get(&self, x: i64, y: i64) -> Option<u32> {
         //std::dbg!((x, y));
         //std::dbg!(self.bytes.len());
@@ -171,6 +237,7 @@ impl Image {
         }
     }
 
+    #[allow(clippy::needless_late_init)]
     pub fn plot_line_width(&mut self, (x1, y1): (i64, i64), (x2, y2): (i64, i64), wd: f64) {
         /* Bresenham's line algorithm */
         let mut d;
@@ -215,7 +282,7 @@ impl Image {
                     let mut _x = x;
                     loop {
                         let t = total(_x);
-                        if t < -1 * delta || t >= delta {
+                        if t < -delta || t >= delta {
                             break;
                         }
                         _x += 1;
@@ -224,7 +291,7 @@ impl Image {
                     let mut _x = x;
                     loop {
                         let t = total(_x);
-                        if t < -1 * delta || t >= delta {
+                        if t < -delta || t >= delta {
                             break;
                         }
                         _x -= 1;
@@ -235,11 +302,11 @@ impl Image {
                     return;
                 }
                 if d >= 0 {
-                    y = y + sy;
-                    d = d - ax;
+                    y += sy;
+                    d -= ax;
                 }
-                x = x + sx;
-                d = d + ay;
+                x += sx;
+                d += ay;
             }
         } else {
             /* y step */
@@ -258,7 +325,7 @@ impl Image {
                     let mut _x = x;
                     loop {
                         let t = total(_x);
-                        if t < -1 * delta || t >= delta {
+                        if t < -delta || t >= delta {
                             break;
                         }
                         _x += 1;
@@ -267,7 +334,7 @@ impl Image {
                     let mut _x = x;
                     loop {
                         let t = total(_x);
-                        if t < -1 * delta || t >= delta {
+                        if t < -delta || t >= delta {
                             break;
                         }
                         _x -= 1;
@@ -278,11 +345,164 @@ impl Image {
                     return;
                 }
                 if d >= 0 {
-                    x = x + sx;
-                    d = d - ay;
+                    x += sx;
+                    d -= ay;
                 }
-                y = y + sy;
-                d = d + ax;
+                y += sy;
+                d += ax;
+            }
+        }
+    }
+
+    /// Plots a line with a `pen x pen` square brush.
+    pub fn plot_line_pen(&mut self, (x0, y0): (i64, i64), (x1, y1): (i64, i64), pen: i64) {
+        /* Bresenham's line algorithm */
+        let dx = (x1 - x0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let dy = -(y1 - y0).abs();
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        let (mut x, mut y) = (x0, y0);
+        loop {
+            for by in (y - pen / 2)..(y - pen / 2 + pen) {
+                for bx in (x - pen / 2)..(x - pen / 2 + pen) {
+                    if self.get(bx, by).is_some() {
+                        self.plot(bx, by);
+                    }
+                }
+            }
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    /// Plots an anti-aliased line `wd` pixels wide, with round ends.
+    pub fn plot_line_width_aa(&mut self, (x0, y0): (f64, f64), (x1, y1): (f64, f64), wd: f64) {
+        let r = wd / 2.0;
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let len2 = dx * dx + dy * dy;
+        let (left, right) = ((x0.min(x1) - r).floor(), (x0.max(x1) + r).ceil());
+        let (top, bottom) = ((y0.min(y1) - r).floor(), (y0.max(y1) + r).ceil());
+        for y in (top as i64)..=(bottom as i64) {
+            for x in (left as i64)..=(right as i64) {
+                let (px, py) = (x as f64 - x0, y as f64 - y0);
+                /* distance of the pixel center from the segment */
+                let t = if len2 == 0.0 {
+                    0.0
+                } else {
+                    ((px * dx + py * dy) / len2).clamp(0.0, 1.0)
+                };
+                let d = f64::hypot(px - t * dx, py - t * dy);
+                self.plot_aa(x, y, r + 0.5 - d);
+            }
+        }
+    }
+
+    /// Plots an anti-aliased ellipse with sub-pixel center and radii, Wu style: the curve is
+    /// walked along x where it is flat and along y where it is steep. With `fill` the inside is
+    /// covered too.
+    ///
+    /// `quadrants` select the same quarters as in [`Image::plot_ellipse`].
+    pub fn plot_ellipse_aa(
+        &mut self,
+        (xm, ym): (f64, f64),
+        (a, b): (f64, f64),
+        quadrants: [bool; 4],
+        fill: bool,
+    ) {
+        if a <= 0.0 || b <= 0.0 {
+            return;
+        }
+        /* signs of x and y in each quadrant, see plot_ellipse() */
+        const SIGNS: [(f64, f64); 4] = [(1.0, 1.0), (-1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)];
+        let cover = |exact: f64, k: f64| -> f64 {
+            if fill {
+                exact - k + 0.5
+            } else {
+                1.0 - (exact - k).abs()
+            }
+        };
+        /* where the slope of the curve is 1 */
+        let (a2, b2) = (a * a, b * b);
+        let x_turn = a2 / f64::sqrt(a2 + b2);
+        let y_turn = b2 / f64::sqrt(a2 + b2);
+
+        for (q, &(sx, sy)) in SIGNS.iter().enumerate() {
+            if !quadrants[q] {
+                continue;
+            }
+            /* pixels whose center is at offset `from..=to` from the center along a quadrant axis */
+            let pixels = |center: f64, sign: f64, from: f64, to: f64| {
+                let (p0, p1) = (center + sign * from, center + sign * to);
+                (p0.min(p1).ceil() as i64)..=(p0.max(p1).floor() as i64)
+            };
+            /* flat part: one column at a time, the curve is at height y */
+            for px in pixels(xm, sx, -0.5, x_turn + 0.5) {
+                let x = sx * (px as f64 - xm);
+                let y = b * f64::sqrt((1.0 - x * x / a2).max(0.0));
+                let from = if fill { -0.5 } else { y - 1.0 };
+                for py in pixels(ym, sy, from, y + 1.0) {
+                    self.plot_aa(px, py, cover(y, sy * (py as f64 - ym)));
+                }
+            }
+            /* steep part: one row at a time, the curve is at x */
+            for py in pixels(ym, sy, -0.5, y_turn + 0.5) {
+                let y = sy * (py as f64 - ym);
+                let x = a * f64::sqrt((1.0 - y * y / b2).max(0.0));
+                let from = if fill { -0.5 } else { x - 1.0 };
+                for px in pixels(xm, sx, from, x + 1.0) {
+                    self.plot_aa(px, py, cover(x, sx * (px as f64 - xm)));
+                }
+            }
+        }
+    }
+
+    /// Fills a polygon with anti-aliased edges; pixels are covered by how far their center is
+    /// inside of the nearest edge.
+    pub fn fill_polygon_aa(&mut self, points: &[(f64, f64)]) {
+        if points.len() < 3 {
+            return;
+        }
+        let (mut left, mut top) = (f64::MAX, f64::MAX);
+        let (mut right, mut bottom) = (f64::MIN, f64::MIN);
+        for &(x, y) in points {
+            left = left.min(x);
+            right = right.max(x);
+            top = top.min(y);
+            bottom = bottom.max(y);
+        }
+        for y in (top.floor() as i64 - 1)..=(bottom.ceil() as i64 + 1) {
+            for x in (left.floor() as i64 - 1)..=(right.ceil() as i64 + 1) {
+                let (px, py) = (x as f64, y as f64);
+                let mut inside = false;
+                let mut d = f64::MAX;
+                for i in 0..points.len() {
+                    let (ax, ay) = points[i];
+                    let (bx, by) = points[(i + 1) % points.len()];
+                    /* even-odd rule */
+                    if (ay > py) != (by > py) && px < ax + (py - ay) * (bx - ax) / (by - ay) {
+                        inside = !inside;
+                    }
+                    let (dx, dy) = (bx - ax, by - ay);
+                    let len2 = dx * dx + dy * dy;
+                    let t = if len2 == 0.0 {
+                        0.0
+                    } else {
+                        (((px - ax) * dx + (py - ay) * dy) / len2).clamp(0.0, 1.0)
+                    };
+                    d = d.min(f64::hypot(px - ax - t * dx, py - ay - t * dy));
+                }
+                self.plot_aa(x, y, if inside { 0.5 + d } else { 0.5 - d });
             }
         }
     }
@@ -351,3 +571,38 @@ impl Image {
         }
     }
 }
+
+#[test]
+fn test_antialias() {
+    let mut image = Image::new(20, 20, 0, 0);
+    /* a one pixel wide horizontal line on pixel centers is solid, and ends there */
+    image.plot_line_width_aa((2.0, 5.0), (17.0, 5.0), 1.0);
+    assert!((2..=17).all(|x| image.get(x, 5) == Some(BLACK)));
+    assert_eq!(image.get(1, 5), Some(WHITE));
+    assert_eq!(image.get(10, 4), Some(WHITE));
+    /* halfway between two rows it covers both by half */
+    image.clear();
+    image.plot_line_width_aa((2.0, 5.5), (17.0, 5.5), 1.0);
+    assert!((coverage(image.get(10, 5).unwrap()) - 0.5).abs() < 0.05);
+    assert_eq!(image.get(10, 5), image.get(10, 6));
+
+    /* a filled disc is solid inside, soft on its edge, and nothing outside */
+    image.clear();
+    image.plot_ellipse_aa((10.0, 10.0), (5.3, 5.3), [true; 4], true);
+    assert_eq!(image.get(10, 10), Some(BLACK));
+    assert_eq!(image.get(13, 13), Some(BLACK));
+    assert!((0.0..1.0).contains(&coverage(image.get(15, 10).unwrap())));
+    assert!(coverage(image.get(15, 10).unwrap()) > 0.0);
+    assert_eq!(image.get(17, 10), Some(WHITE));
+    assert_eq!(image.get(14, 14), Some(WHITE));
+
+    /* partial pixels are composited over what is already in the buffer */
+    let mut buffer = vec![AZURE_BLUE; 400];
+    image.draw_scaled(&mut buffer, 20, 1, BLACK, None);
+    assert_eq!(buffer[10 * 20 + 10], BLACK);
+    assert_eq!(buffer[0], AZURE_BLUE);
+    assert_eq!(
+        buffer[10 * 20 + 15],
+        blend(AZURE_BLUE, BLACK, coverage(image.get(15, 10).unwrap()))
+    );
+}
@@ -0,0 +1,4 @@
+const _A_WIDTH: usize =  5;
+const _A_HEIGHT: usize = 7;
+const _A_BITS: &[u8] = &[
+   0x0e, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11 ];
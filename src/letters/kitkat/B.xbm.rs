@@ -0,0 +1,4 @@
+const _B_WIDTH: usize =  5;
+const _B_HEIGHT: usize = 7;
+const _B_BITS: &[u8] = &[
+   0x0f, 0x11, 0x11, 0x0f, 0x11, 0x11, 0x0f ];
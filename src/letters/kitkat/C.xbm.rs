@@ -0,0 +1,4 @@
+const _C_WIDTH: usize =  5;
+const _C_HEIGHT: usize = 7;
+const _C_BITS: &[u8] = &[
+   0x0e, 0x11, 0x01, 0x01, 0x01, 0x11, 0x0e ];
@@ -0,0 +1,4 @@
+const _D_WIDTH: usize =  5;
+const _D_HEIGHT: usize = 7;
+const _D_BITS: &[u8] = &[
+   0x0f, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0f ];
@@ -0,0 +1,4 @@
+const _F_WIDTH: usize =  5;
+const _F_HEIGHT: usize = 7;
+const _F_BITS: &[u8] = &[
+   0x1f, 0x01, 0x01, 0x0f, 0x01, 0x01, 0x01 ];
@@ -0,0 +1,4 @@
+const _G_WIDTH: usize =  5;
+const _G_HEIGHT: usize = 7;
+const _G_BITS: &[u8] = &[
+   0x0e, 0x11, 0x01, 0x1d, 0x11, 0x11, 0x1e ];
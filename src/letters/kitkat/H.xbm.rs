@@ -0,0 +1,4 @@
+const _H_WIDTH: usize =  5;
+const _H_HEIGHT: usize = 7;
+const _H_BITS: &[u8] = &[
+   0x11, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11 ];
@@ -0,0 +1,4 @@
+const _I_WIDTH: usize =  3;
+const _I_HEIGHT: usize = 7;
+const _I_BITS: &[u8] = &[
+   0x07, 0x02, 0x02, 0x02, 0x02, 0x02, 0x07 ];
@@ -0,0 +1,4 @@
+const _J_WIDTH: usize =  5;
+const _J_HEIGHT: usize = 7;
+const _J_BITS: &[u8] = &[
+   0x1c, 0x08, 0x08, 0x08, 0x08, 0x09, 0x06 ];
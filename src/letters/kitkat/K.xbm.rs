@@ -0,0 +1,4 @@
+const _K_WIDTH: usize =  5;
+const _K_HEIGHT: usize = 7;
+const _K_BITS: &[u8] = &[
+   0x11, 0x09, 0x05, 0x03, 0x05, 0x09, 0x11 ];
@@ -0,0 +1,4 @@
+const _L_WIDTH: usize =  5;
+const _L_HEIGHT: usize = 7;
+const _L_BITS: &[u8] = &[
+   0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x1f ];
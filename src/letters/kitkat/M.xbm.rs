@@ -0,0 +1,4 @@
+const _M_WIDTH: usize =  5;
+const _M_HEIGHT: usize = 7;
+const _M_BITS: &[u8] = &[
+   0x11, 0x1b, 0x15, 0x15, 0x11, 0x11, 0x11 ];
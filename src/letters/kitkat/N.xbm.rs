@@ -0,0 +1,4 @@
+const _N_WIDTH: usize =  5;
+const _N_HEIGHT: usize = 7;
+const _N_BITS: &[u8] = &[
+   0x11, 0x11, 0x13, 0x15, 0x19, 0x11, 0x11 ];
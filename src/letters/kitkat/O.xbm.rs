@@ -0,0 +1,4 @@
+const _O_WIDTH: usize =  5;
+const _O_HEIGHT: usize = 7;
+const _O_BITS: &[u8] = &[
+   0x0e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e ];
@@ -0,0 +1,4 @@
+const _P_WIDTH: usize =  5;
+const _P_HEIGHT: usize = 7;
+const _P_BITS: &[u8] = &[
+   0x0f, 0x11, 0x11, 0x0f, 0x01, 0x01, 0x01 ];
@@ -0,0 +1,4 @@
+const _Q_WIDTH: usize =  5;
+const _Q_HEIGHT: usize = 7;
+const _Q_BITS: &[u8] = &[
+   0x0e, 0x11, 0x11, 0x11, 0x15, 0x09, 0x16 ];
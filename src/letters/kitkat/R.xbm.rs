@@ -0,0 +1,4 @@
+const _R_WIDTH: usize =  5;
+const _R_HEIGHT: usize = 7;
+const _R_BITS: &[u8] = &[
+   0x0f, 0x11, 0x11, 0x0f, 0x05, 0x09, 0x11 ];
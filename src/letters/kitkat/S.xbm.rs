@@ -0,0 +1,4 @@
+const _S_WIDTH: usize =  5;
+const _S_HEIGHT: usize = 7;
+const _S_BITS: &[u8] = &[
+   0x1e, 0x01, 0x01, 0x0e, 0x10, 0x10, 0x0f ];
@@ -0,0 +1,4 @@
+const _T_WIDTH: usize =  5;
+const _T_HEIGHT: usize = 7;
+const _T_BITS: &[u8] = &[
+   0x1f, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04 ];
@@ -0,0 +1,4 @@
+const _U_WIDTH: usize =  5;
+const _U_HEIGHT: usize = 7;
+const _U_BITS: &[u8] = &[
+   0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e ];
@@ -0,0 +1,4 @@
+const _V_WIDTH: usize =  5;
+const _V_HEIGHT: usize = 7;
+const _V_BITS: &[u8] = &[
+   0x11, 0x11, 0x11, 0x11, 0x11, 0x0a, 0x04 ];
@@ -0,0 +1,4 @@
+const _W_WIDTH: usize =  5;
+const _W_HEIGHT: usize = 7;
+const _W_BITS: &[u8] = &[
+   0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0a ];
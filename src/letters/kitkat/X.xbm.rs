@@ -0,0 +1,4 @@
+const _X_WIDTH: usize =  5;
+const _X_HEIGHT: usize = 7;
+const _X_BITS: &[u8] = &[
+   0x11, 0x11, 0x0a, 0x04, 0x0a, 0x11, 0x11 ];
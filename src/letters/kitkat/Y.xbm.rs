@@ -0,0 +1,4 @@
+const _Y_WIDTH: usize =  5;
+const _Y_HEIGHT: usize = 7;
+const _Y_BITS: &[u8] = &[
+   0x11, 0x11, 0x0a, 0x04, 0x04, 0x04, 0x04 ];
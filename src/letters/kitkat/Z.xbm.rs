@@ -0,0 +1,4 @@
+const _Z_WIDTH: usize =  5;
+const _Z_HEIGHT: usize = 7;
+const _Z_BITS: &[u8] = &[
+   0x1f, 0x10, 0x08, 0x04, 0x02, 0x01, 0x1f ];
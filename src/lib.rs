@@ -0,0 +1,700 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::f64;
+use std::f64::consts::{FRAC_PI_2, PI};
+use std::time::Duration;
+
+mod image;
+pub use image::*;
+mod draw;
+pub use draw::*;
+mod clock;
+pub use clock::*;
+pub mod alarm;
+pub mod audio;
+pub mod blink;
+pub mod chime;
+mod date;
+pub mod gif;
+pub mod hands;
+pub mod moonphase;
+pub mod png;
+pub mod pomodoro;
+mod renderer;
+pub mod solar;
+pub mod svg;
+mod theme;
+pub use theme::*;
+pub mod timer;
+#[cfg(unix)]
+pub mod tty;
+pub mod tz;
+pub use renderer::*;
+mod wall;
+pub use wall::*;
+
+pub const fn from_u8_rgb(r: u8, g: u8, b: u8) -> u32 {
+    let (r, g, b) = (r as u32, g as u32, b as u32);
+    (r << 16) | (g << 8) | b
+}
+pub const AZURE_BLUE: u32 = from_u8_rgb(0, 127, 255);
+pub(crate) const _RED: u32 = from_u8_rgb(157, 37, 10);
+pub const WHITE: u32 = from_u8_rgb(255, 255, 255);
+pub const MOON: u32 = from_u8_rgb(0xd4, 0xc6, 0xa8);
+pub const MOONDARK: u32 = from_u8_rgb(0x59, 0x53, 0x45);
+pub const SUN: u32 = from_u8_rgb(0xff, 0xeb, 0x3b);
+pub const SUNDARK: u32 = from_u8_rgb(0xff, 0xa3, 0x01);
+pub const BLACK: u32 = 0;
+
+#[derive(Clone, Copy)]
+struct Bitmap<'bits> {
+    bits: &'bits [u8],
+    width: usize,
+    height: usize,
+    x_offset: usize,
+    y_offset: usize,
+}
+
+#[inline(always)]
+pub fn pixel_width_to_bits_width(i: usize) -> usize {
+    i.wrapping_div(8) + if i.wrapping_rem(8) > 0 { 1 } else { 0 }
+}
+
+pub fn bits_to_bytes(bits: &[u8], width: usize) -> Vec<u32> {
+    let mut ret = Vec::with_capacity(bits.len() * 8);
+    let mut current_row_count = 0;
+    for byte in bits {
+        for n in 0..8 {
+            if byte.rotate_right(n) & 0x01 > 0 {
+                ret.push(BLACK);
+            } else {
+                ret.push(WHITE);
+            }
+            current_row_count += 1;
+            if current_row_count == width {
+                current_row_count = 0;
+                break;
+            }
+        }
+    }
+    ret
+}
+
+impl<'bits> Bitmap<'bits> {
+    /*
+    fn draw(&self, buffer: &mut Vec<u32>, fg: u32, bg: Option<u32>) {
+        let row_width = pixel_width_to_bits_width(self.width);
+        //std::dbg!(row_width);
+        debug_assert_eq!(row_width * self.height, self.bits.len());
+        let mut bits = self.bits.iter();
+        let mut i = self.y_offset * (self.width);
+        for _ in 0..self.height {
+            let mut c = 0;
+            'byte_row: for byte in bits.by_ref().take(row_width) {
+                for n in 0..8 {
+                    if self.x_offset + c == self.width || i + self.x_offset + c >= buffer.len() {
+                        break 'byte_row;
+                    }
+                    if byte.rotate_right(n) & 0x01 > 0 {
+                        buffer[i + self.x_offset + c] = fg;
+                    } else if let Some(bg) = bg {
+                        buffer[i + self.x_offset + c] = bg;
+                    };
+                    c += 1;
+                }
+            }
+            i += self.width + self.x_offset;
+        }
+        //debug_assert_eq!(i, self.width * self.height);
+    }
+    */
+}
+
+include!("catback.rs");
+const CATBACK: Bitmap<'static> = Bitmap {
+    bits: CAT_BITS,
+    width: CAT_WIDTH,
+    height: CAT_HEIGHT,
+    x_offset: 0,
+    y_offset: 0,
+};
+
+include!("cattie.rs");
+const CATTIE: Bitmap<'static> = Bitmap {
+    bits: CATTIE_BITS,
+    width: CATTIE_WIDTH,
+    height: CATTIE_HEIGHT,
+    x_offset: 0,
+    y_offset: 0,
+};
+
+include!("catwhite.rs");
+const CATWHITE: Bitmap<'static> = Bitmap {
+    bits: CATWHITE_BITS,
+    width: CATWHITE_WIDTH,
+    height: CATWHITE_HEIGHT,
+    x_offset: 0,
+    y_offset: 0,
+};
+
+include!("eyes.rs");
+const EYES: Bitmap<'static> = Bitmap {
+    bits: EYES_BITS,
+    width: EYES_WIDTH,
+    height: EYES_HEIGHT,
+    x_offset: CAT_WIDTH / 2,
+    y_offset: 30,
+};
+
+include!("tail.rs");
+const TAIL: Bitmap<'static> = Bitmap {
+    bits: TAIL_BITS,
+    width: TAIL_WIDTH,
+    height: TAIL_HEIGHT,
+    x_offset: 0,
+    y_offset: TAIL_OFFSET_Y,
+};
+
+include!("octaveback.xbm.rs");
+const OCTAVEBACK: Bitmap<'static> = Bitmap {
+    bits: OCTAVEBACK_BITS,
+    width: OCTAVEBACK_WIDTH,
+    height: OCTAVEBACK_HEIGHT,
+    x_offset: 0,
+    y_offset: 0,
+};
+
+include!("octavetail.xbm.rs");
+const OCTAVETAIL: Bitmap<'static> = Bitmap {
+    bits: OCTAVETAIL_BITS,
+    width: OCTAVETAIL_WIDTH,
+    height: OCTAVETAIL_HEIGHT,
+    x_offset: 34,
+    y_offset: 238,
+};
+
+pub const NUM_TAILS: usize = 10;
+
+const N_TAIL_PTS: usize = 7;
+
+/*
+ *  The moving parts below take an integer "scale" and are drawn at that resolution, with their
+ *  offsets in scaled framebuffer coordinates.
+ */
+/* The outline of the left eye, in a pixmap 30 * scale pixels high. At scales above 1 the top
+ * of the eye projects to just above the first row, so it is clamped to it. */
+fn eye_outline(t: f64, scale: usize) -> Vec<(i64, i64)> {
+    macro_rules! tr {
+        ($cond:expr ,? $then:expr ,: $else:expr) => {
+            if $cond {
+                $then
+            } else {
+                $else
+            }
+        };
+    }
+    let s = scale as f64;
+
+    const A: f64 = 0.7;
+    let omega: f64 = 1.0;
+    let phi: f64 = 3.0 * FRAC_PI_2;
+    let mut u: f64;
+    let w: f64 = FRAC_PI_2;
+    /*  Sphere parameters    */
+    /*  Radius               */
+    let r: f64 = 1.0;
+    /*  Center of sphere     */
+    let x0: f64 = 0.0;
+    let y0: f64 = 0.0;
+    let z0: f64 = 2.0;
+
+    let angle: f64 = A * f64::sin(omega * t + phi) + w;
+    let mut points: Vec<(i64, i64)> = Vec::with_capacity(100);
+
+    u = -FRAC_PI_2;
+    while u < FRAC_PI_2 {
+        let x = x0 + r * f64::cos(u) * f64::cos(angle + PI / 7.0);
+        let z = z0 + r * f64::cos(u) * f64::sin(angle + PI / 7.0);
+        let y = y0 + r * f64::sin(u);
+
+        let a = ((tr!(z == 0.0 ,? x ,: x / z) * 23.0 * s) + 12.0 * s) as i64;
+        let b = ((tr!(z == 0.0 ,? y ,: y / z) * 23.0 * s) + 11.0 * s) as i64;
+        points.push((a, b));
+        u += 0.25 / s;
+    }
+
+    u = FRAC_PI_2;
+    while u > -FRAC_PI_2 {
+        let x = x0 + r * f64::cos(u) * f64::cos(angle - PI / 7.0);
+        let z = z0 + r * f64::cos(u) * f64::sin(angle - PI / 7.0);
+        let y = y0 + r * f64::sin(u);
+
+        let a = ((tr!(z == 0.0 ,? x ,: x / z) * 23.0 * s) + 12.0 * s) as i64;
+        let b = ((tr!(z == 0.0 ,? y ,: y / z) * 23.0 * s) + 11.0 * s) as i64;
+        points.push((a, b));
+        u -= 0.25 / s;
+    }
+
+    let bottom = (30 * scale) as i64 - 1;
+    for point in points.iter_mut() {
+        point.1 = point.1.clamp(0, bottom);
+    }
+    points
+}
+
+fn create_eye_pixmap(t: f64, dog: bool, scale: usize) -> Image {
+    let mut ret = Image::new(
+        60 * scale,
+        30 * scale,
+        (47 + if dog { 5 } else { 0 }) * scale,
+        (30 + if dog { 10 } else { 0 }) * scale,
+    );
+
+    //ret.draw_outline();
+
+    let mut points = eye_outline(t, scale);
+
+    let (mut cx, mut cy) = points[0]; // calculate centroid of points
+    for window in points.as_slice().windows(2) {
+        let point_a = window[0];
+        let point_b = window[1];
+        cx += point_b.0;
+        cy += point_b.1;
+        ret.plot_line_width(point_a, point_b, 0.);
+    }
+    let n = points.len() as i64;
+    ret.flood_fill(cx / n, cy / n);
+    for point in points.iter_mut() {
+        point.0 += 31 * scale as i64;
+    }
+    let (mut cx, mut cy) = points[0]; // calculate centroid of points
+    for window in points.as_slice().windows(2) {
+        let point_a = window[0];
+        let point_b = window[1];
+        cx += point_b.0;
+        cy += point_b.1;
+        ret.plot_line_width(point_a, point_b, 0.);
+    }
+    ret.flood_fill(cx / n, cy / n);
+
+    ret
+}
+
+/*
+ *  The pupils wide open and round, staring ahead from the middle of the eyes of
+ *  create_eye_pixmap.
+ */
+fn create_wide_eyes_pixmap(dog: bool, scale: usize) -> Image {
+    let s = scale as i64;
+    let mut ret = Image::new(
+        60 * scale,
+        30 * scale,
+        (47 + if dog { 5 } else { 0 }) * scale,
+        (30 + if dog { 10 } else { 0 }) * scale,
+    );
+    let (rx, ry) = (6 * s, 8 * s);
+    for cx in [12 * s, 43 * s] {
+        for y in -ry..=ry {
+            for x in -rx..=rx {
+                if (x * x * ry * ry + y * y * rx * rx) <= rx * rx * ry * ry {
+                    ret.plot(cx + x, 11 * s + y);
+                }
+            }
+        }
+    }
+    ret
+}
+
+/*
+ * Upper lids over the eyes, covering closed (0.0 to 1.0) of the height of the eyes, drawn in
+ * the colour of the eyes over an eye pixmap.
+ */
+fn create_lids_pixmap(closed: f64, dog: bool, scale: usize) -> Image {
+    let s = scale as f64;
+    let mut ret = Image::new(
+        60 * scale,
+        30 * scale,
+        (47 + if dog { 5 } else { 0 }) * scale,
+        (30 + if dog { 10 } else { 0 }) * scale,
+    );
+    let (rx, ry) = (13.0 * s, 12.0 * s);
+    for cx in [13.0 * s, 45.0 * s] {
+        for x in (cx - rx) as i64..=(cx + rx) as i64 {
+            let dx = (x as f64 + 0.5 - cx) / rx;
+            if dx.abs() >= 1.0 {
+                continue;
+            }
+            /* each column of the eye is covered from its top */
+            let half_height = ry * (1.0 - dx * dx).sqrt();
+            let top = 11.5 * s - half_height;
+            for y in (top as i64).max(0)..(top + closed * 2.0 * half_height) as i64 {
+                ret.plot(x, y);
+            }
+        }
+    }
+    ret
+}
+
+#[allow(clippy::needless_late_init)]
+fn dog_tail(t: f64, scale: usize) -> Image {
+    /*  Pendulum parameters */
+    let s: f64;
+    let c: f64;
+    const A: f64 = 0.4;
+    let omega: f64 = 1.0;
+    let phi: f64 = 3.0 * FRAC_PI_2;
+    let angle: f64;
+
+    /*
+     *  Compute pendulum function.
+     */
+    angle = A * f64::sin(omega * t + phi);
+    s = f64::sin(angle);
+    c = f64::cos(angle);
+
+    let mut buf = Image::new(
+        OCTAVETAIL_WIDTH * scale,
+        (OCTAVETAIL_HEIGHT + 4) * scale,
+        OCTAVETAIL.x_offset * scale,
+        OCTAVETAIL.y_offset * scale,
+    );
+    /*
+     *  Rotate the center tail about its origin by "angle" degrees.
+     */
+    let t = bits_to_bytes(OCTAVETAIL_BITS, OCTAVETAIL_WIDTH);
+    let center_point = (40 * scale as i64, 0);
+    for y in 0..(OCTAVETAIL_HEIGHT * scale) {
+        for x in 0..(OCTAVETAIL_WIDTH * scale) {
+            if t[(y / scale) * OCTAVETAIL_WIDTH + x / scale] == BLACK {
+                let x = (x as i64 - center_point.0) as f64;
+                let y = (y as i64 - center_point.1) as f64;
+                let xr = (x * c - y * s) as i64 + center_point.0;
+                let yr = (x * s + y * c) as i64 + center_point.1;
+                /* the tip of the swung tail sticks out of the pixmap, where it is not drawn */
+                for x in [xr, xr + 1] {
+                    if x >= 0 && yr >= 0 {
+                        buf.plot(x, yr);
+                    }
+                }
+            }
+        }
+    }
+    buf
+}
+
+/*
+ *  Compute pendulum function.
+ */
+fn pendulum_angle(t: f64) -> f64 {
+    /*  Pendulum parameters */
+    const A: f64 = 0.4;
+    let omega: f64 = 1.0;
+    let phi: f64 = 3.0 * FRAC_PI_2;
+
+    A * f64::sin(omega * t + phi)
+}
+
+/*
+ *  Rotate a point about the origin by "angle" radians.
+ */
+fn rotate((x, y): (f64, f64), angle: f64) -> (f64, f64) {
+    let sin_theta = f64::sin(angle);
+    let cos_theta = f64::cos(angle);
+    (
+        x * cos_theta + (y * sin_theta),
+        -x * sin_theta + (y * cos_theta),
+    )
+}
+
+//    static XPoint tailOffset = { 74, -15 };
+const DROP_TAIL_WIDTH: usize = 90;
+const DROP_TAIL_HEIGHT: usize = 80;
+const DROP_TAIL_OFFSET: (i64, i64) = ((DROP_TAIL_WIDTH / 2) as i64, 0);
+const DROP_TAIL_X_OFFSET: usize = CAT_WIDTH / 2 - DROP_TAIL_WIDTH / 2;
+/* Width of the drop at the end of the tail */
+const DROP_WIDTH: f64 = 17.0;
+/*  "Center" tail points definition */
+const DROP_CENTER_TAIL: [(i64, i64); 3] = [
+    (0, 0),
+    (3, (DROP_TAIL_HEIGHT - 15) as i64),
+    (20, (DROP_TAIL_HEIGHT - 15) as i64),
+];
+
+#[allow(clippy::needless_late_init)]
+fn create_tail_image(t: f64, scale: usize, antialias: bool) -> Image {
+    let angle: f64;
+    let s = scale as f64;
+
+    let mut new_tail: Vec<(i64, i64)> = vec![(0, 0); 3]; /*  Tail at time "t"  */
+
+    angle = pendulum_angle(t);
+
+    let mut buf = Image::new(
+        DROP_TAIL_WIDTH * scale,
+        DROP_TAIL_HEIGHT * scale,
+        DROP_TAIL_X_OFFSET * scale,
+        TAIL.y_offset * scale,
+    );
+    let width2: f64 = s * DROP_WIDTH / 2.0;
+    if antialias {
+        let points = DROP_CENTER_TAIL
+            .iter()
+            .map(|&(x, y)| {
+                let (x, y) = rotate((x as f64 * s, y as f64 * s), angle);
+                (
+                    x + DROP_TAIL_OFFSET.0 as f64 * s,
+                    y + DROP_TAIL_OFFSET.1 as f64 * s,
+                )
+            })
+            .collect::<Vec<(f64, f64)>>();
+        buf.fill_polygon_aa(&points);
+        let last_point = (
+            (points[1].0 + points[2].0) / 2.0,
+            (points[1].1 + points[2].1) / 2.0,
+        );
+        buf.plot_ellipse_aa(last_point, (width2, width2), [true; 4], true);
+        return buf;
+    }
+
+    /*
+     *  Rotate the center tail about its origin by "angle" degrees.
+     */
+    for i in 0..3 {
+        let (x, y) = rotate(
+            (
+                (DROP_CENTER_TAIL[i].0 * scale as i64) as f64,
+                (DROP_CENTER_TAIL[i].1 * scale as i64) as f64,
+            ),
+            angle,
+        );
+        new_tail[i] = (x as i64, y as i64);
+
+        new_tail[i].0 += DROP_TAIL_OFFSET.0 * scale as i64;
+        new_tail[i].1 += DROP_TAIL_OFFSET.1 * scale as i64;
+    }
+
+    buf.plot_line_width(new_tail[0], new_tail[1], 0.0);
+    buf.plot_line_width(new_tail[1], new_tail[2], 0.0);
+    buf.plot_line_width(new_tail[2], new_tail[0], 0.0);
+
+    let center = (
+        (new_tail[0].0 + new_tail[1].0 + new_tail[2].0) / 3,
+        (new_tail[0].1 + new_tail[1].1 + new_tail[2].1) / 3,
+    );
+    buf.flood_fill(center.0, center.1);
+
+    let (xa, ya) = new_tail[1];
+    let (xb, yb) = new_tail[2];
+    let last_point = ((xa + xb) / 2, (ya + yb) / 2);
+    buf.plot_ellipse(
+        last_point,
+        (width2 as i64, width2 as i64),
+        [true, true, true, true],
+        1.0,
+    );
+    buf.flood_fill(
+        last_point.0 + 5 * scale as i64,
+        last_point.1 + 5 * scale as i64,
+    );
+    buf
+}
+
+/// Exact outline of the drop shaped tail at time `t`, in framebuffer coordinates (pixel centers
+/// are at `.5`).
+pub fn tail_shapes(t: f64) -> Vec<svg::Shape> {
+    let angle = pendulum_angle(t);
+    let points = DROP_CENTER_TAIL
+        .iter()
+        .map(|&(x, y)| {
+            let (x, y) = rotate((x as f64, y as f64), angle);
+            (
+                x + (DROP_TAIL_OFFSET.0 as usize + DROP_TAIL_X_OFFSET) as f64 + 0.5,
+                y + (DROP_TAIL_OFFSET.1 as usize + TAIL.y_offset) as f64 + 0.5,
+            )
+        })
+        .collect::<Vec<(f64, f64)>>();
+    let center = (
+        (points[1].0 + points[2].0) / 2.0,
+        (points[1].1 + points[2].1) / 2.0,
+    );
+    vec![
+        svg::Shape::Polygon { points, fill: true },
+        svg::Shape::Circle {
+            center,
+            radius: DROP_WIDTH / 2.0,
+        },
+    ]
+}
+
+//    static XPoint tailOffset = { 74, -15 };
+const HOOK_TAIL_OFFSET: (i64, i64) = (72, 0);
+const HOOK_CENTER_TAIL: [(i64, i64); N_TAIL_PTS] = [
+    /*  "Center" tail points definition */
+    (0, 0),
+    (0, 76),
+    (3, 82),
+    (10, 84),
+    (18, 82),
+    (21, 76),
+    (21, 70),
+];
+/*
+ *  The tail has a hook to it.  A real pendulum so shaped would
+ *  hang a bit to the left (as you look at the cat).
+ */
+const HOOK_OFF_CENTER_ANGLE: f64 = -0.08;
+const HOOK_WIDTH: f64 = 15.0;
+
+#[allow(clippy::needless_late_init)]
+fn create_tail_image_hook(t: f64, scale: usize, antialias: bool) -> Image {
+    let angle: f64;
+    let s = scale as f64;
+
+    let mut off_center_tail: Vec<(i64, i64)> = vec![(0, 0); N_TAIL_PTS]; /* off center tail    */
+    let mut new_tail: Vec<(i64, i64)> = vec![(0, 0); N_TAIL_PTS]; /*  Tail at time "t"  */
+
+    /*
+     *  Create an "off-center" tail to deal with the fact that
+     *  the tail has a hook to it.
+     */
+    for i in 0..N_TAIL_PTS {
+        let (x, y) = rotate(
+            (
+                (HOOK_CENTER_TAIL[i].0 * scale as i64) as f64,
+                (HOOK_CENTER_TAIL[i].1 * scale as i64) as f64,
+            ),
+            HOOK_OFF_CENTER_ANGLE,
+        );
+        off_center_tail[i] = (x as i64, y as i64);
+    }
+
+    angle = pendulum_angle(t);
+
+    let (width, height) = (TAIL.width * scale, TAIL.height * scale);
+    if antialias {
+        let mut buf = Image::new(width, height, TAIL.x_offset * scale, TAIL.y_offset * scale);
+        /* same place as the bitmap tail below, which draw::plot() shifts one byte right */
+        let points = off_center_tail
+            .iter()
+            .map(|&(x, y)| {
+                let (x, y) = rotate((x as f64, y as f64), angle);
+                (
+                    x + (HOOK_TAIL_OFFSET.0 + 8) as f64 * s,
+                    y + HOOK_TAIL_OFFSET.1 as f64 * s,
+                )
+            })
+            .collect::<Vec<(f64, f64)>>();
+        for window in points.windows(2) {
+            buf.plot_line_width_aa(window[0], window[1], s * HOOK_WIDTH);
+        }
+        return buf;
+    }
+    let mut ret = if scale == 1 {
+        TAIL.bits.to_vec()
+    } else {
+        vec![0; pixel_width_to_bits_width(width) * height]
+    };
+    let mut buf = Buffer {
+        vec: &mut ret,
+        row_width: width,
+        height,
+    };
+    /*
+     *  Rotate the center tail about its origin by "angle" degrees.
+     */
+    for i in 0..N_TAIL_PTS {
+        let (x, y) = rotate(
+            (off_center_tail[i].0 as f64, off_center_tail[i].1 as f64),
+            angle,
+        );
+        new_tail[i] = (x as i64, y as i64);
+
+        /* draw::plot() puts pixels one byte to the right, keep that shift proportional */
+        new_tail[i].0 += HOOK_TAIL_OFFSET.0 * scale as i64 + 8 * (scale as i64 - 1);
+        new_tail[i].1 += HOOK_TAIL_OFFSET.1 * scale as i64;
+    }
+
+    let width2: f64 = s * HOOK_WIDTH / 2.0;
+    for window in new_tail.as_slice().windows(2) {
+        let point_a = window[0];
+        let point_b = window[1];
+        plot_line_with_width(&mut buf, point_a, point_b, s * HOOK_WIDTH);
+    }
+
+    let mut last_point = *new_tail.last().unwrap();
+    last_point.1 += scale as i64;
+    for b in 0..=((0.8 * width2) as i64) {
+        plot_ellipse(
+            &mut buf,
+            last_point,
+            (width2 as i64, b),
+            [false, false, true, true],
+            1.0,
+        );
+    }
+
+    Image::from(Bitmap {
+        bits: &ret,
+        width,
+        height,
+        x_offset: TAIL.x_offset * scale,
+        y_offset: TAIL.y_offset * scale,
+    })
+}
+
+/// Exact outline of the hooked tail at time `t`, in framebuffer coordinates (pixel centers are at
+/// `.5`).
+pub fn tail_shapes_hook(t: f64) -> Vec<svg::Shape> {
+    let angle = pendulum_angle(t);
+    let points = HOOK_CENTER_TAIL
+        .iter()
+        .map(|&(x, y)| {
+            let (x, y) = rotate(rotate((x as f64, y as f64), HOOK_OFF_CENTER_ANGLE), angle);
+            /* the bitmap tail is shifted one byte right by draw::plot(), match it */
+            (
+                x + (HOOK_TAIL_OFFSET.0 as usize + 8 + TAIL.x_offset) as f64 + 0.5,
+                y + (HOOK_TAIL_OFFSET.1 as usize + TAIL.y_offset) as f64 + 0.5,
+            )
+        })
+        .collect::<Vec<(f64, f64)>>();
+    vec![svg::Shape::Polyline {
+        points,
+        width: HOOK_WIDTH,
+    }]
+}
+
+#[test]
+fn test_eye_pixmap() {
+    for scale in [2, 3] {
+        for frame in 0..NUM_TAILS {
+            let t = frame as f64 * PI / (NUM_TAILS as f64);
+            let (width, height) = (60 * scale as i64, 30 * scale as i64);
+            for (x, y) in eye_outline(t, scale) {
+                /* both eyes, the right one being 31 pixels to the right */
+                assert!((0..width).contains(&x) && (0..width).contains(&(x + 31 * scale as i64)));
+                assert!((0..height).contains(&y), "{} at scale {}", y, scale);
+            }
+            let eyes = create_eye_pixmap(t, false, scale);
+            assert!(eyes.bytes[..eyes.width].contains(&BLACK));
+        }
+    }
+}
@@ -19,465 +19,17 @@
  * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use minifb::{Key, Window, WindowOptions};
-use std::f64;
-use std::f64::consts::{FRAC_PI_2, PI};
-use std::time::{Duration, Instant, SystemTime};
-
-mod image;
-pub use image::*;
-mod draw;
-pub use draw::*;
-mod date;
-mod hands;
-mod moonphase;
-
-pub const fn from_u8_rgb(r: u8, g: u8, b: u8) -> u32 {
-    let (r, g, b) = (r as u32, g as u32, b as u32);
-    (r << 16) | (g << 8) | b
-}
-pub(crate) const AZURE_BLUE: u32 = from_u8_rgb(0, 127, 255);
-pub(crate) const _RED: u32 = from_u8_rgb(157, 37, 10);
-pub(crate) const WHITE: u32 = from_u8_rgb(255, 255, 255);
-pub(crate) const MOON: u32 = from_u8_rgb(0xd4, 0xc6, 0xa8);
-pub(crate) const MOONDARK: u32 = from_u8_rgb(0x59, 0x53, 0x45);
-pub(crate) const SUN: u32 = from_u8_rgb(0xff, 0xeb, 0x3b);
-pub(crate) const SUNDARK: u32 = from_u8_rgb(0xff, 0xa3, 0x01);
-pub(crate) const BLACK: u32 = 0;
-
-#[derive(Clone, Copy)]
-struct Bitmap<'bits> {
-    bits: &'bits [u8],
-    width: usize,
-    height: usize,
-    x_offset: usize,
-    y_offset: usize,
-}
-
-#[inline(always)]
-pub fn pixel_width_to_bits_width(i: usize) -> usize {
-    i.wrapping_div(8) + if i.wrapping_rem(8) > 0 { 1 } else { 0 }
-}
-
-pub fn bits_to_bytes(bits: &[u8], width: usize) -> Vec<u32> {
-    let mut ret = Vec::with_capacity(bits.len() * 8);
-    let mut current_row_count = 0;
-    for byte in bits {
-        for n in 0..8 {
-            if byte.rotate_right(n) & 0x01 > 0 {
-                ret.push(BLACK);
-            } else {
-                ret.push(WHITE);
-            }
-            current_row_count += 1;
-            if current_row_count == width {
-                current_row_count = 0;
-                break;
-            }
-        }
-    }
-    ret
-}
-
-impl<'bits> Bitmap<'bits> {
-    /*
-    fn draw(&self, buffer: &mut Vec<u32>, fg: u32, bg: Option<u32>) {
-        let row_width = pixel_width_to_bits_width(self.width);
-        //std::dbg!(row_width);
-        debug_assert_eq!(row_width * self.height, self.bits.len());
-        let mut bits = self.bits.iter();
-        let mut i = self.y_offset * (self.width);
-        for _ in 0..self.height {
-            let mut c = 0;
-            'byte_row: for byte in bits.by_ref().take(row_width) {
-                for n in 0..8 {
-                    if self.x_offset + c == self.width || i + self.x_offset + c >= buffer.len() {
-                        break 'byte_row;
-                    }
-                    if byte.rotate_right(n) & 0x01 > 0 {
-                        buffer[i + self.x_offset + c] = fg;
-                    } else if let Some(bg) = bg {
-                        buffer[i + self.x_offset + c] = bg;
-                    };
-                    c += 1;
-                }
-            }
-            i += self.width + self.x_offset;
-        }
-        //debug_assert_eq!(i, self.width * self.height);
-    }
-    */
-}
-
-include!("catback.rs");
-const CATBACK: Bitmap<'static> = Bitmap {
-    bits: CAT_BITS,
-    width: CAT_WIDTH,
-    height: CAT_HEIGHT,
-    x_offset: 0,
-    y_offset: 0,
-};
-
-include!("cattie.rs");
-const CATTIE: Bitmap<'static> = Bitmap {
-    bits: CATTIE_BITS,
-    width: CATTIE_WIDTH,
-    height: CATTIE_HEIGHT,
-    x_offset: 0,
-    y_offset: 0,
-};
-
-include!("catwhite.rs");
-const CATWHITE: Bitmap<'static> = Bitmap {
-    bits: CATWHITE_BITS,
-    width: CATWHITE_WIDTH,
-    height: CATWHITE_HEIGHT,
-    x_offset: 0,
-    y_offset: 0,
-};
-
-include!("eyes.rs");
-const EYES: Bitmap<'static> = Bitmap {
-    bits: EYES_BITS,
-    width: EYES_WIDTH,
-    height: EYES_HEIGHT,
-    x_offset: CAT_WIDTH / 2,
-    y_offset: 30,
-};
-
-include!("tail.rs");
-const TAIL: Bitmap<'static> = Bitmap {
-    bits: TAIL_BITS,
-    width: TAIL_WIDTH,
-    height: TAIL_HEIGHT,
-    x_offset: 0,
-    y_offset: TAIL_OFFSET_Y,
-};
-
-include!("octaveback.xbm.rs");
-const OCTAVEBACK: Bitmap<'static> = Bitmap {
-    bits: OCTAVEBACK_BITS,
-    width: OCTAVEBACK_WIDTH,
-    height: OCTAVEBACK_HEIGHT,
-    x_offset: 0,
-    y_offset: 0,
-};
-
-include!("octavetail.xbm.rs");
-const OCTAVETAIL: Bitmap<'static> = Bitmap {
-    bits: OCTAVETAIL_BITS,
-    width: OCTAVETAIL_WIDTH,
-    height: OCTAVETAIL_HEIGHT,
-    x_offset: 34,
-    y_offset: 238,
-};
-
-const NUM_TAILS: usize = 10;
-
-const N_TAIL_PTS: usize = 7;
-
-fn create_eye_pixmap(t: f64, dog: bool) -> Image {
-    macro_rules! tr {
-        ($cond:expr ,? $then:expr ,: $else:expr) => {
-            if $cond {
-                $then
-            } else {
-                $else
-            }
-        };
-    }
-    let mut ret = Image {
-        bytes: vec![WHITE; 30 * 60],
-        width: 60,
-        height: 30,
-        x_offset: 47 + if dog { 5 } else { 0 },
-        y_offset: 30 + if dog { 10 } else { 0 },
-    };
-
-    //ret.draw_outline();
-
-    const A: f64 = 0.7;
-    let omega: f64 = 1.0;
-    let phi: f64 = 3.0 * FRAC_PI_2;
-    let mut u: f64;
-    let w: f64 = FRAC_PI_2;
-    /*  Sphere parameters    */
-    /*  Radius               */
-    let r: f64 = 1.0;
-    /*  Center of sphere     */
-    let x0: f64 = 0.0;
-    let y0: f64 = 0.0;
-    let z0: f64 = 2.0;
-
-    let angle: f64 = A * f64::sin(omega * t + phi) + w;
-    let mut points: Vec<(i64, i64)> = Vec::with_capacity(100);
-
-    let mut i = 0;
-    u = -1.0 * FRAC_PI_2;
-    while u < FRAC_PI_2 {
-        let x = x0 + r * f64::cos(u) * f64::cos(angle + PI / 7.0);
-        let z = z0 + r * f64::cos(u) * f64::sin(angle + PI / 7.0);
-        let y = y0 + r * f64::sin(u);
-
-        let a = ((tr!(z == 0.0 ,? x ,: x / z) * 23.0) + 12.0) as i64;
-        let b = ((tr!(z == 0.0 ,? y ,: y / z) * 23.0) + 11.0) as i64;
-        points.push((a, b));
-        u += 0.25;
-        i += 1;
-    }
-
-    u = FRAC_PI_2;
-    while u > -1.0 * FRAC_PI_2 {
-        let x = x0 + r * f64::cos(u) * f64::cos(angle - PI / 7.0);
-        let z = z0 + r * f64::cos(u) * f64::sin(angle - PI / 7.0);
-        let y = y0 + r * f64::sin(u);
-
-        let a = ((tr!(z == 0.0 ,? x ,: x / z) * 23.0) + 12.0) as i64;
-        let b = ((tr!(z == 0.0 ,? y ,: y / z) * 23.0) + 11.0) as i64;
-        points.push((a, b));
-        u -= 0.25;
-        i += 1;
-    }
-
-    let (mut cx, mut cy) = points[0]; // calculate centroid of points
-    for window in points.as_slice().windows(2) {
-        let point_a = window[0];
-        let point_b = window[1];
-        cx += point_b.0;
-        cy += point_b.1;
-        ret.plot_line_width(point_a, point_b, 0.);
-    }
-    let n = points.len() as i64;
-    ret.flood_fill(cx / n, cy / n);
-    for j in 0..i {
-        points[j].0 += 31;
-    }
-    let (mut cx, mut cy) = points[0]; // calculate centroid of points
-    for window in points.as_slice().windows(2) {
-        let point_a = window[0];
-        let point_b = window[1];
-        cx += point_b.0;
-        cy += point_b.1;
-        ret.plot_line_width(point_a, point_b, 0.);
-    }
-    ret.flood_fill(cx / n, cy / n);
-
-    ret
-}
-
-fn dog_tail(t: f64) -> Image {
-    /*  Pendulum parameters */
-    let s: f64;
-    let c: f64;
-    const A: f64 = 0.4;
-    let omega: f64 = 1.0;
-    let phi: f64 = 3.0 * FRAC_PI_2;
-    let angle: f64;
-
-    /*
-     *  Compute pendulum function.
-     */
-    angle = A * f64::sin(omega * t + phi);
-    s = f64::sin(angle);
-    c = f64::cos(angle);
-
-    let mut buf = Image::new(
-        OCTAVETAIL_WIDTH,
-        OCTAVETAIL_HEIGHT + 4,
-        OCTAVETAIL.x_offset,
-        OCTAVETAIL.y_offset,
-    );
-    /*
-     *  Rotate the center tail about its origin by "angle" degrees.
-     */
-    let t = bits_to_bytes(OCTAVETAIL_BITS, OCTAVETAIL_WIDTH);
-    let center_point = (40, 0);
-    for y in 0..OCTAVETAIL_HEIGHT {
-        for x in 0..OCTAVETAIL_WIDTH {
-            if t[y * OCTAVETAIL_WIDTH + x] == BLACK {
-                let x = (x as i64 - center_point.0) as f64;
-                let y = (y as i64 - center_point.1) as f64;
-                let xr = (x * c - y * s) as i64 + center_point.0;
-                let yr = (x * s + y * c) as i64 + center_point.1;
-                buf.plot(xr, yr);
-                buf.plot(xr + 1, yr);
-            }
-        }
-    }
-    buf
-}
-
-fn create_tail_image(t: f64) -> Image {
-    /*  Pendulum parameters */
-    let sin_theta: f64;
-    let cos_theta: f64;
-    const A: f64 = 0.4;
-    let omega: f64 = 1.0;
-    let phi: f64 = 3.0 * FRAC_PI_2;
-    let angle: f64;
-
-    //    static XPoint tailOffset = { 74, -15 };
-    const TAIL_WIDTH: usize = 90;
-    const TAIL_HEIGHT: usize = 80;
-    const TAIL_OFFSET: (i64, i64) = ((TAIL_WIDTH / 2) as i64, 0);
-    let mut center_tail: Vec<(i64, i64)> = vec![(0, 0); 3]; /* center tail    */
-    let mut new_tail: Vec<(i64, i64)> = vec![(0, 0); 3]; /*  Tail at time "t"  */
-
-    {
-        /*
-         *  Create an "center" tail.
-         */
-        center_tail[0] = (0, 0);
-        center_tail[1] = (3, (TAIL_HEIGHT - 15) as i64);
-        center_tail[2] = (20, (TAIL_HEIGHT - 15) as i64);
-    }
-
-    /*
-     *  Compute pendulum function.
-     */
-    angle = A * f64::sin(omega * t + phi);
-    sin_theta = f64::sin(angle);
-    cos_theta = f64::cos(angle);
-
-    let mut buf = Image::new(
-        TAIL_WIDTH,
-        TAIL_HEIGHT,
-        CAT_WIDTH / 2 - TAIL_WIDTH / 2,
-        TAIL.y_offset,
-    );
-    /*
-     *  Rotate the center tail about its origin by "angle" degrees.
-     */
-    for i in 0..3 {
-        new_tail[i].0 = ((center_tail[i].0 as f64) * cos_theta
-            + ((center_tail[i].1 as f64) * sin_theta)) as i64;
-        new_tail[i].1 = ((center_tail[i].0 as f64 * -1.0) * sin_theta
-            + ((center_tail[i].1 as f64) * cos_theta)) as i64;
-
-        new_tail[i].0 += TAIL_OFFSET.0;
-        new_tail[i].1 += TAIL_OFFSET.1;
-    }
-
-    const WIDTH: f64 = 17.0;
-    const WIDTH2: f64 = WIDTH / 2.0;
-    buf.plot_line_width(new_tail[0], new_tail[1], 0.0);
-    buf.plot_line_width(new_tail[1], new_tail[2], 0.0);
-    buf.plot_line_width(new_tail[2], new_tail[0], 0.0);
-
-    let center = (
-        (new_tail[0].0 + new_tail[1].0 + new_tail[2].0) / 3,
-        (new_tail[0].1 + new_tail[1].1 + new_tail[2].1) / 3,
-    );
-    buf.flood_fill(center.0, center.1);
-
-    let (xa, ya) = new_tail[1];
-    let (xb, yb) = new_tail[2];
-    let last_point = ((xa + xb) / 2, (ya + yb) / 2);
-    buf.plot_ellipse(
-        last_point,
-        (WIDTH2 as i64, WIDTH2 as i64),
-        [true, true, true, true],
-        1.0,
-    );
-    buf.flood_fill(last_point.0 + 5, last_point.1 + 5);
-    buf
-}
-
-fn create_tail_image_hook(t: f64) -> Image {
-    /*  Pendulum parameters */
-    let mut sin_theta: f64;
-    let mut cos_theta: f64;
-    const A: f64 = 0.4;
-    let omega: f64 = 1.0;
-    let phi: f64 = 3.0 * FRAC_PI_2;
-    let mut angle: f64;
-
-    //    static XPoint tailOffset = { 74, -15 };
-    const TAIL_OFFSET: (i64, i64) = (72, 0);
-    const CENTER_TAIL: [(i64, i64); N_TAIL_PTS] = [
-        /*  "Center" tail points definition */
-        (0, 0),
-        (0, 76),
-        (3, 82),
-        (10, 84),
-        (18, 82),
-        (21, 76),
-        (21, 70),
-    ];
-
-    let mut off_center_tail: Vec<(i64, i64)> = vec![(0, 0); N_TAIL_PTS]; /* off center tail    */
-    let mut new_tail: Vec<(i64, i64)> = vec![(0, 0); N_TAIL_PTS]; /*  Tail at time "t"  */
-
-    {
-        /*
-         *  Create an "off-center" tail to deal with the fact that
-         *  the tail has a hook to it.  A real pendulum so shaped would
-         *  hang a bit to the left (as you look at the cat).
-         */
-        angle = -0.08;
-        sin_theta = f64::sin(angle);
-        cos_theta = f64::cos(angle);
-
-        for i in 0..N_TAIL_PTS {
-            off_center_tail[i].0 = ((CENTER_TAIL[i].0 as f64) * cos_theta
-                + ((CENTER_TAIL[i].1 as f64) * sin_theta))
-                as i64;
-            off_center_tail[i].1 = ((-1.0 * (CENTER_TAIL[i].0 as f64)) * sin_theta
-                + ((CENTER_TAIL[i].1 as f64) * cos_theta))
-                as i64;
-        }
-    }
-
-    /*
-     *  Compute pendulum function.
-     */
-    angle = A * f64::sin(omega * t + phi);
-    sin_theta = f64::sin(angle);
-    cos_theta = f64::cos(angle);
-
-    let mut ret = TAIL.bits.to_vec();
-    let mut buf = Buffer {
-        vec: &mut ret,
-        row_width: TAIL.width,
-        height: TAIL.height,
-    };
-    /*
-     *  Rotate the center tail about its origin by "angle" degrees.
-     */
-    for i in 0..N_TAIL_PTS {
-        new_tail[i].0 = ((off_center_tail[i].0 as f64) * cos_theta
-            + ((off_center_tail[i].1 as f64) * sin_theta)) as i64;
-        new_tail[i].1 = ((off_center_tail[i].0 as f64 * -1.0) * sin_theta
-            + ((off_center_tail[i].1 as f64) * cos_theta)) as i64;
-
-        new_tail[i].0 += TAIL_OFFSET.0;
-        new_tail[i].1 += TAIL_OFFSET.1;
-    }
-
-    const WIDTH: f64 = 15.0;
-    const WIDTH2: f64 = WIDTH / 2.0;
-    for window in new_tail.as_slice().windows(2) {
-        let point_a = window[0];
-        let point_b = window[1];
-        plot_line_with_width(&mut buf, point_a, point_b, WIDTH as _);
-    }
-
-    let mut last_point = *new_tail.last().unwrap();
-    last_point.1 += 1;
-    for b in 0..=((0.8 * WIDTH2) as i64) {
-        plot_ellipse(
-            &mut buf,
-            last_point,
-            (WIDTH2 as i64, b),
-            [false, false, true, true],
-            1.0,
-        );
-    }
-
-    Image::from(Bitmap { bits: &ret, ..TAIL })
-}
-
-const HELP: &str = r#"Usage: kitkat [--hook|--crazy|--offset OFFSET|--borderless|--resize|--sunmoon|--moon|--date]
+use kitkat::blink::{self, Blinker};
+use kitkat::chime::{Chime, QuietHours};
+use kitkat::pomodoro::{self, Pomodoro};
+use kitkat::timer::{self, Timer};
+#[cfg(unix)]
+use kitkat::tty::Terminal;
+use kitkat::*;
+use minifb::{Key, KeyRepeat, ScaleMode, Window, WindowOptions};
+use std::time::Duration;
+
+const HELP: &str = r#"Usage: kitkat [--hook|--crazy|--offset OFFSET|--tz ZONE|--clocks CLOCKS|--borderless|--resize|--scale N|--antialias|--sweep|--theme THEME|--auto-dark|--location LAT,LON|--hemisphere SIDE|--sunmoon|--moon|--moon-percent|--date|--alarm TIME|--alarm-sound FILE|--alarms FILE|--chime|--chime-command COMMAND|--quiet-hours FROM-TO|--timer DURATION|--stopwatch|--pomodoro|--pomodoro-lengths LENGTHS|--pomodoro-log FILE|--snapshot FILE|--tty|--gif FILE|--svg FILE|--at TIME]
 
 Displays a kit kat clock with the system time, or the system time with given offset if the --offset
 argument is provided.
@@ -486,26 +38,76 @@ argument is provided.
       --crazy                go faster for each time this argument is invoked
       --offset OFFSET        add OFFSET to current system time (only the first given
                              offset will be used)
+      --tz ZONE              show the time in time zone ZONE of the system's tz database, e.g.
+                             Europe/Athens, following its daylight saving time changes
+      --clocks CLOCKS        show a row of clocks in one window, CLOCKS is a comma separated list
+                             of LABEL=ZONE with a caption and a --tz ZONE for each clock, e.g.
+                             "Athens=Europe/Athens,NYC=America/New_York"
       --borderless
-      --resize
+      --resize               allow resizing the window, redrawing the clock at the largest integer
+                             zoom that fits
+      --scale N              draw the clock N times larger, with crisp pixels (N <= 16)
+      --antialias            draw the hands and tail with smooth edges
+      --sweep                sweep the second and minute hands round smoothly instead of ticking
+      --theme THEME          colour the clock with THEME, one of classic, dark, solarized,
+                             high-contrast, or the path of a theme file
+      --auto-dark            switch to the dark theme at night, when --sunmoon shows the moon
+                             (with --clocks, when it is night at the first clock)
+      --location LAT,LON     tell night from day by sunrise and sunset at latitude LAT and
+                             longitude LON in degrees, e.g. 60.17,24.94 (default is night from
+                             19:00 to 08:00)
+      --hemisphere SIDE      draw the moon as seen from the north or south SIDE of the equator
+                             (default is the side of --location, or north)
       --sunmoon              show sun or moon phase depending on the hour
       --moon                 show only moon phase
+      --moon-percent         write how much of the moon is lit under it
       --date                 show month date
       --dog                  show an italian greyhound named Gaius Octavius Maximus instead of a cat
+      --alarm TIME           ring an alarm at TIME in HH:MM format every day, or only on some days
+                             with HH:MM@DAYS, e.g. 07:30@weekdays or 09:00@mon,wed,fri, until a
+                             key is pressed (can be given more than once)
+      --alarm-sound FILE     play the WAV file FILE while an alarm of --alarm rings
+      --alarms FILE          read alarms from FILE, an [[alarm]] table for each with a time and
+                             optional days and sound, e.g. time = "07:30"
+      --chime                strike the hours like a cuckoo clock: the tail swings once for each
+                             hour and the cat blinks at each swing
+      --chime-command COMMAND
+                             run COMMAND with the shell when the hour strikes, e.g. to play a
+                             sound, with KITKAT_HOUR and KITKAT_STRIKES in its environment
+      --quiet-hours FROM-TO  don't strike the hours from FROM to TO, in HH:MM or HH format, e.g.
+                             22:00-07:00
+      --timer DURATION       show the time left of a countdown from DURATION on the face instead
+                             of the time, e.g. 25m, 90s or 1h30m
+      --stopwatch            show the time run by a stopwatch on the face instead of the time
+      --pomodoro             alternate work and breaks, showing the time left of each on the face
+                             and how much is done around it, with a red tie at work and a green
+                             one on breaks
+      --pomodoro-lengths LENGTHS
+                             set the Pomodoro lengths to WORK,SHORT,LONG,SESSIONS: how long work
+                             and short and long breaks last, and work sessions before a long
+                             break (default 25m,5m,15m,4)
+      --pomodoro-log FILE    log ended Pomodoro phases to FILE (default
+                             $XDG_DATA_HOME/kitkat/pomodoro.log)
+      --tty                  draw in the terminal with 24-bit colours instead of opening a window
+      --snapshot FILE        write a single frame as a PNG image to FILE and exit without opening
+                             a window
+      --gif FILE             write a looping GIF of a full tail swing to FILE and exit without
+                             opening a window
+      --svg FILE             write a single frame as an SVG image to FILE and exit without opening
+                             a window
+      --at TIME              show TIME instead of the system time, in HH:MM:SS or HH:MM format
 
       OFFSET format is [+-]{0,1}\d\d:\d\d, e.g: 02:00 or -03:45 or +00:00
+      Space pauses and restarts a timer, stopwatch or Pomodoro phase, and r resets it.
 "#;
 
+const MAX_SCALE: usize = 16;
+
+/// Tail frames to swing by in a frame while an alarm rings or a timer is over, as with
+/// `--crazy --crazy --crazy`.
+const RINGING_SPEED: usize = 3;
+
 fn main() {
-    let time = unsafe { libc::time(std::ptr::null_mut()) };
-    let mut tm = std::mem::MaybeUninit::<libc::tm>::uninit();
-    unsafe {
-        #[cfg(target_os = "windows")]
-        libc::localtime_s(tm.as_mut_ptr(), &time as *const _);
-        #[cfg(not(target_os = "windows"))]
-        libc::localtime_r(&time as *const _, tm.as_mut_ptr());
-    }
-    let mut tm = unsafe { tm.assume_init() };
     let args = std::env::args().skip(1).collect::<Vec<String>>();
     if !args.is_empty() && args.iter().any(|s| s == "--help") {
         if args.iter().any(|s| s != "--help") {
@@ -521,23 +123,31 @@ fn main() {
     let resize = !args.is_empty() && args.iter().any(|s| s == "--resize");
     let sunmoon = !args.is_empty() && args.iter().any(|s| s == "--sunmoon");
     let moon = !args.is_empty() && args.iter().any(|s| s == "--moon");
+    let moon_percent = !args.is_empty() && args.iter().any(|s| s == "--moon-percent");
     if sunmoon && moon {
         eprintln!("ERROR: You can't use both --sunmoon and --moon.");
         return;
     }
     let show_date = !args.is_empty() && args.iter().any(|s| s == "--date");
     let dog = !args.is_empty() && args.iter().any(|s| s == "--dog");
+    let antialias = !args.is_empty() && args.iter().any(|s| s == "--antialias");
+    let sweep = !args.is_empty() && args.iter().any(|s| s == "--sweep");
+    let tty = !args.is_empty() && args.iter().any(|s| s == "--tty");
+    if tty && cfg!(not(unix)) {
+        eprintln!("ERROR: --tty is only supported on unix terminals.");
+        return;
+    }
 
-    let mut tail_kind: fn(_) -> _ = create_tail_image;
+    let mut tail = TailKind::Drop;
     let crazy: usize = args.iter().filter(|s| *s == "--crazy").count();
     if !args.is_empty() && args.iter().any(|s| s == "--hook") {
-        tail_kind = create_tail_image_hook;
+        tail = TailKind::Hook;
     }
     let mut offset_sign = true;
     let mut offset_hour = 0;
     let mut offset_min = 0;
     if let Some(pos) = args.iter().position(|s| s == "--offset") {
-        if let Some(offset) = args.iter().nth(pos + 1) {
+        if let Some(offset) = args.get(pos + 1) {
             let mut offset = offset.as_str();
             if offset.starts_with("+") {
                 offset = &offset[1..];
@@ -574,284 +184,314 @@ fn main() {
             return;
         }
     }
-    let mut buffer: Vec<u32> = vec![WHITE; CAT_WIDTH * CAT_HEIGHT];
-    let mut tails_frames: Vec<Image> = Vec::with_capacity(NUM_TAILS);
-    let mut eyes_frames: Vec<Image> = Vec::with_capacity(NUM_TAILS);
-
-    for i in 0..NUM_TAILS {
-        if dog {
-            tails_frames.push(dog_tail(i as f64 * PI / (NUM_TAILS as f64)));
-        } else {
-            tails_frames.push(tail_kind(i as f64 * PI / (NUM_TAILS as f64)));
+    let zone = match flag_value(&args, "--tz", "ZONE").map(tz::TimeZone::load) {
+        None => None,
+        Some(Ok(zone)) => Some(zone),
+        Some(Err(err)) => {
+            eprintln!("ERROR: {}", err);
+            return;
         }
-        eyes_frames.push(create_eye_pixmap(i as f64 * PI / (NUM_TAILS as f64), dog));
+    };
+    if zone.is_some() && args.iter().any(|s| s == "--offset") {
+        eprintln!("ERROR: You can't use both --offset and --tz.");
+        return;
     }
+    let mut timekeeper = Timekeeper::new(SystemClock, zone.map_or(Zone::Local, Zone::Tz));
+    let offset = (offset_hour * 3600 + offset_min * 60) as i64;
+    timekeeper.set_offset(if offset_sign { offset } else { -offset });
+    if let Some(pos) = args.iter().position(|s| s == "--at") {
+        match args.get(pos + 1).and_then(|at| parse_hms(at)) {
+            Some((hour, minutes, seconds)) => timekeeper.set_time_of_day(hour, minutes, seconds),
+            None => {
+                eprintln!("--at requires a time of day in the format HH:MM:SS or HH:MM");
+                return;
+            }
+        }
+    }
+    let time = timekeeper.now();
+    let gif = flag_value(&args, "--gif", "FILE");
+    let svg = flag_value(&args, "--svg", "FILE");
+    let snapshot = flag_value(&args, "--snapshot", "FILE");
+
+    let theme = match flag_value(&args, "--theme", "THEME").map(Theme::load) {
+        None => Theme::default(),
+        Some(Ok(theme)) => theme,
+        Some(Err(err)) => {
+            eprintln!("ERROR: {}", err);
+            return;
+        }
+    };
 
-    let mut window = Window::new(
-        "kitkat - ESC or q to exit",
-        CAT_WIDTH,
-        CAT_HEIGHT,
-        WindowOptions {
-            title: true,
-            borderless,
-            resize,
-            transparency: false,
-            ..WindowOptions::default()
-        },
-    )
-    .unwrap_or_else(|e| {
-        panic!("{}", e);
-    });
-
-    // Limit to max ~60 fps update rate
-    window.limit_update_rate(Some(std::time::Duration::from_micros(16600)));
-
-    let catwhite = Image::from(CATWHITE);
-    catwhite.draw(&mut buffer, WHITE, Some(WHITE));
-    let catback = if dog {
-        Image::from(OCTAVEBACK)
-    } else {
-        Image::from(CATBACK)
+    let location = match flag_value(&args, "--location", "LAT,LON").map(solar::Location::parse) {
+        None => None,
+        Some(Some(location)) => Some(location),
+        Some(None) => {
+            eprintln!("--location requires a latitude and longitude in degrees, e.g. 60.17,24.94");
+            return;
+        }
     };
-    catback.draw(&mut buffer, BLACK, None);
-    if !dog {
-        let cattie = Image::from(CATTIE);
-        cattie.draw(&mut buffer, AZURE_BLUE, None);
-    }
-    let tail = if dog {
-        Image::from(OCTAVETAIL)
-    } else {
-        Image::from(TAIL)
+    let hemisphere = match flag_value(&args, "--hemisphere", "SIDE") {
+        None => None,
+        Some("north") => Some(moonphase::Hemisphere::Northern),
+        Some("south") => Some(moonphase::Hemisphere::Southern),
+        Some(_) => {
+            eprintln!("--hemisphere requires north or south");
+            return;
+        }
     };
-    tail.draw(&mut buffer, WHITE, None);
-    let eyes = Image::from(EYES);
-    eyes.draw(&mut buffer, BLACK, None);
-
-    //CATTIE.draw(&mut buffer, AZURE_BLUE, None);
-    //TAIL.draw(&mut buffer, black, None);
-    //EYES.draw(&mut buffer, black, None);
-    /*
-     */
-
-    let _blank_face = Image {
-        bytes: vec![WHITE; hands::FACE_WIDTH * hands::FACE_HEIGHT],
-        width: hands::FACE_WIDTH,
-        height: hands::FACE_HEIGHT,
-        x_offset: hands::FACE_OFFSET_X,
-        y_offset: hands::FACE_OFFSET_Y,
+    let alarms = match parse_alarms(&args)
+        .and_then(|alarms| alarm::AlarmClock::new(alarms, audio::Player::default()))
+    {
+        Ok(alarms) => alarms,
+        Err(err) => {
+            eprintln!("ERROR: {}", err);
+            return;
+        }
     };
-    let mut hour_hand = Image {
-        bytes: vec![WHITE; hands::FACE_WIDTH * hands::FACE_HEIGHT],
-        width: hands::FACE_WIDTH,
-        height: hands::FACE_HEIGHT,
-        x_offset: hands::FACE_OFFSET_X,
-        y_offset: hands::FACE_OFFSET_Y,
+    let chime = !args.is_empty() && args.iter().any(|s| s == "--chime");
+    let quiet = match flag_value(&args, "--quiet-hours", "FROM-TO").map(QuietHours::parse) {
+        None => None,
+        Some(Some(quiet)) => Some(quiet),
+        Some(None) => {
+            eprintln!("--quiet-hours requires a range in HH:MM or HH format, e.g. 22:00-07:00");
+            return;
+        }
     };
-    let mut minute_hand = Image {
-        bytes: vec![WHITE; hands::FACE_WIDTH * hands::FACE_HEIGHT],
-        width: hands::FACE_WIDTH,
-        height: hands::FACE_HEIGHT,
-        x_offset: hands::FACE_OFFSET_X,
-        y_offset: hands::FACE_OFFSET_Y,
+    let chime = chime.then(|| Chime::new(quiet));
+    let chime_command = flag_value(&args, "--chime-command", "COMMAND").map(str::to_string);
+    let stopwatch = !args.is_empty() && args.iter().any(|s| s == "--stopwatch");
+    let mode = match (flag_value(&args, "--timer", "DURATION"), stopwatch) {
+        (Some(_), true) => {
+            eprintln!("ERROR: You can't use both --timer and --stopwatch.");
+            return;
+        }
+        (Some(duration), false) => match timer::parse_duration(duration) {
+            Some(duration) => Some(timer::Mode::Countdown(duration)),
+            None => {
+                eprintln!("--timer requires a duration like 25m, 90s or 1h30m");
+                return;
+            }
+        },
+        (None, true) => Some(timer::Mode::Stopwatch),
+        (None, false) => None,
     };
-    let mut second_hand = Image {
-        bytes: vec![WHITE; hands::FACE_WIDTH * hands::FACE_HEIGHT],
-        width: hands::FACE_WIDTH,
-        height: hands::FACE_HEIGHT,
-        x_offset: hands::FACE_OFFSET_X,
-        y_offset: hands::FACE_OFFSET_Y,
+    let timer = mode.map(|mode| {
+        let mut timer = Timer::new(SystemClock, mode);
+        timer.start();
+        timer
+    });
+    let pomodoro = !args.is_empty() && args.iter().any(|s| s == "--pomodoro");
+    if pomodoro && timer.is_some() {
+        eprintln!("ERROR: You can't use --pomodoro with --timer or --stopwatch.");
+        return;
+    }
+    let settings = match flag_value(&args, "--pomodoro-lengths", "LENGTHS") {
+        None => pomodoro::Settings::default(),
+        Some(lengths) => match pomodoro::Settings::parse(lengths) {
+            Some(settings) => settings,
+            None => {
+                eprintln!(
+                    "--pomodoro-lengths requires WORK,SHORT,LONG,SESSIONS, e.g. 50m,10m,30m,3"
+                );
+                return;
+            }
+        },
+    };
+    let pomodoro = pomodoro.then(|| Pomodoro::new(SystemClock, settings));
+    let pomodoro_log = flag_value(&args, "--pomodoro-log", "FILE")
+        .map(std::path::PathBuf::from)
+        .or_else(default_pomodoro_log);
+    let auto_dark = !args.is_empty() && args.iter().any(|s| s == "--auto-dark");
+    /* The theme to show at a given time of day. */
+    let theme_at = |time: &ClockTime| {
+        if auto_dark && is_night(time, location.as_ref()) {
+            Theme::DARK
+        } else {
+            theme
+        }
     };
 
-    const SECOND_HAND_WIDTH: i64 = 19;
-    const SECOND_HAND_HEIGHT: i64 = 0;
-
-    const MINUTE_HAND_WIDTH: i64 = 20;
-    const MINUTE_HAND_HEIGHT: i64 = 3;
-
-    const HOUR_HAND_WIDTH: i64 = 16;
-    const HOUR_HAND_HEIGHT: i64 = 3;
-
-    let mut i: usize = 0;
-    let mut prev_i = i;
-    let mut up = true;
-    let mut system_now_second;
-    let mut now_second = Instant::now();
-    let mut seconds;
-    let _now = SystemTime::now();
-    let mut hour: u8 = tm.tm_hour as _;
-    let mut minutes: u8 = tm.tm_min as _;
-    add_time_offset(
-        &mut hour,
-        &mut minutes,
-        offset_sign,
-        offset_hour,
-        offset_min,
-    );
-    let mut passed_seconds = tm.tm_sec as _;
-    hands::draw_second(
-        &mut second_hand,
-        SECOND_HAND_WIDTH,
-        SECOND_HAND_HEIGHT,
-        -5,
-        (passed_seconds as f64) / 60.0,
-    );
-    second_hand.draw(&mut buffer, BLACK, None);
-    hands::draw_hand(
-        &mut hour_hand,
-        HOUR_HAND_WIDTH,
-        HOUR_HAND_HEIGHT,
-        -5,
-        0.5 * (((hour as usize % 12) * 60 + (minutes as usize)) as f64) / 360.,
-    );
-    hour_hand.draw(&mut buffer, BLACK, None);
-    hands::draw_hand(
-        &mut minute_hand,
-        MINUTE_HAND_WIDTH,
-        MINUTE_HAND_HEIGHT,
-        -5,
-        (6. * (minutes as f64)) / 360.,
-    );
-    minute_hand.draw(&mut buffer, BLACK, None);
+    let scale = match flag_value(&args, "--scale", "N").map(|n| n.parse::<usize>()) {
+        None => 1,
+        Some(Ok(n)) if (1..=MAX_SCALE).contains(&n) => n,
+        Some(_) => {
+            eprintln!("--scale requires an integer between 1 and {}", MAX_SCALE);
+            return;
+        }
+    };
 
-    let sun: Image = moonphase::sun();
-    let sun_bg: Image = moonphase::sun_background();
+    let config = Config {
+        tail,
+        dog,
+        date: show_date,
+        sunmoon,
+        moon,
+        moon_percent,
+        scale,
+        antialias,
+        sweep,
+        theme: with_phase(theme_at(&time), pomodoro.as_ref()),
+        location,
+        hemisphere,
+    };
 
-    let moon_corners: Image = moonphase::corner_fill();
-    let full_moon: Image = moonphase::MoonPosition::FullMoon.into();
-    let moon_phase: Image = moonphase::phase(moonphase::position(None)).into();
+    if let Some(spec) = flag_value(&args, "--clocks", "CLOCKS") {
+        for flag in [
+            "--offset",
+            "--tz",
+            "--at",
+            "--tty",
+            "--gif",
+            "--svg",
+            "--alarm",
+            "--alarms",
+            "--chime",
+            "--timer",
+            "--stopwatch",
+            "--pomodoro",
+        ] {
+            if args.iter().any(|s| s == flag) {
+                eprintln!("ERROR: You can't use both --clocks and {}.", flag);
+                return;
+            }
+        }
+        match parse_clocks(spec) {
+            Ok(clocks) => run_wall(config, &clocks, snapshot, borderless, resize, &theme_at),
+            Err(err) => eprintln!("ERROR: {}", err),
+        }
+        return;
+    }
 
-    let mut date: Image = date::make_date(tm.tm_mday as i64);
+    let mut renderer = ClockRenderer::new(config);
+    renderer.show_duration(
+        timer
+            .as_ref()
+            .or(pomodoro.as_ref().map(Pomodoro::timer))
+            .map(Timer::shown),
+    );
+    renderer.set_progress(pomodoro.as_ref().map(Pomodoro::progress));
+    let mut buffer: Vec<u32> = vec![theme.background; renderer.width() * renderer.height()];
+
+    if let Some(path) = gif {
+        let frames = swing_cycle()
+            .into_iter()
+            .map(|i| renderer.render(&time, i))
+            .collect::<Vec<Vec<u32>>>();
+        if let Err(err) = std::fs::File::create(path).and_then(|f| {
+            gif::write_gif(
+                std::io::BufWriter::new(f),
+                &frames,
+                renderer.width(),
+                renderer.height(),
+                10,
+            )
+        }) {
+            eprintln!("ERROR: Could not write GIF to {}: {}", path, err);
+            std::process::exit(1);
+        }
+        if snapshot.is_none() && svg.is_none() {
+            return;
+        }
+    }
+    if let Some(path) = svg {
+        if let Err(err) = std::fs::write(path, renderer.render_svg(&time, NUM_TAILS / 2)) {
+            eprintln!("ERROR: Could not write SVG to {}: {}", path, err);
+            std::process::exit(1);
+        }
+        if snapshot.is_none() {
+            return;
+        }
+    }
+    if let Some(path) = snapshot {
+        renderer.render_into(&mut buffer, &time, NUM_TAILS / 2);
+        write_snapshot(path, &buffer, renderer.width(), renderer.height());
+        return;
+    }
 
-    while window.is_open() && !window.is_key_down(Key::Escape) && !window.is_key_down(Key::Q) {
-        let cur_tail = &tails_frames[i];
-        tails_frames[prev_i].draw(&mut buffer, WHITE, None);
-        cur_tail.draw(&mut buffer, BLACK, None);
-        let cur_eyes = &eyes_frames[i];
-        eyes_frames[prev_i].draw(&mut buffer, WHITE, None);
-        prev_i = i;
-        cur_eyes.draw(&mut buffer, BLACK, None);
-
-        let new_now_second = Instant::now();
-
-        if crazy > 0 || new_now_second - now_second >= Duration::from_secs(1) {
-            passed_seconds += 1;
-            now_second = new_now_second;
-            system_now_second = SystemTime::now();
-            seconds = system_now_second
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs();
-            //blank_face.draw(&mut buffer, WHITE, Some(WHITE));
-            second_hand.draw(&mut buffer, WHITE, None);
-            second_hand.clear();
-            if crazy > 0 {
-                passed_seconds += 6 * (crazy as u64);
-                seconds = passed_seconds;
+    let (now, nanos) = SystemClock.now_precise();
+    let mut clock = Clock {
+        timekeeper,
+        time,
+        frame: 0,
+        up: true,
+        crazy,
+        hands_offset: 0,
+        alarms,
+        chime,
+        chime_command,
+        timer,
+        pomodoro,
+        pomodoro_log,
+        blinker: Blinker::new(now as u64 ^ (nanos as u64) << 32),
+        lids: Lids::Open,
+    };
+
+    #[cfg(unix)]
+    if tty {
+        let mut terminal = Terminal::new().unwrap_or_else(|e| {
+            panic!("{}", e);
+        });
+        let mut drawn = None;
+        while !terminal.should_quit() {
+            for key in terminal.keys() {
+                clock.press(key.to_ascii_lowercase() as char);
             }
-            hands::draw_second(
-                &mut second_hand,
-                SECOND_HAND_WIDTH,
-                SECOND_HAND_HEIGHT,
-                -5,
-                (seconds as f64) / 60.0,
-            );
-            second_hand.draw(&mut buffer, BLACK, None);
-        }
-        if crazy > 0 || passed_seconds >= 60 {
-            passed_seconds = 0;
-            minutes += 1;
-            if minutes == 60 {
-                minutes = 0;
-                hour += 1;
-                if hour == 24 {
-                    hour = 0;
-                }
-                if show_date && hour <= 2 {
-                    unsafe {
-                        let time = libc::time(std::ptr::null_mut());
-                        #[cfg(target_os = "windows")]
-                        libc::localtime_s(&mut tm as *mut _, &time as *const _);
-                        #[cfg(not(target_os = "windows"))]
-                        libc::localtime_r(&time as *const _, &mut tm as *mut _);
-                    }
-                    date = date::make_date(tm.tm_mday as i64);
-                }
+            let frame = clock.tick();
+            let theme = with_phase(theme_at(&clock.time), clock.pomodoro.as_ref());
+            if theme != renderer.config().theme {
+                renderer.set_theme(theme);
+                drawn = None;
             }
-            minute_hand.draw(&mut buffer, WHITE, None);
-            minute_hand.clear();
-            hands::draw_hand(
-                &mut minute_hand,
-                MINUTE_HAND_WIDTH,
-                MINUTE_HAND_HEIGHT,
-                -5,
-                (6. * (minutes as f64)) / 360.,
-            );
-        }
-        minute_hand.draw(&mut buffer, BLACK, None);
-        //blank_face.draw(&mut buffer, WHITE, Some(WHITE));
-        hour_hand.draw(&mut buffer, WHITE, None);
-        hour_hand.clear();
-        hour_hand.draw(&mut buffer, BLACK, None);
-        if show_date {
-            date.draw(&mut buffer, BLACK, None);
-        }
-        hands::draw_hand(
-            &mut hour_hand,
-            HOUR_HAND_WIDTH,
-            HOUR_HAND_HEIGHT,
-            -5,
-            0.5 * (((hour as usize % 12) * 60 + (minutes as usize)) as f64) / 360.,
-        );
-        hour_hand.draw(&mut buffer, BLACK, None);
-
-        if moon || sunmoon {
-            // FIXME: use the https://en.wikipedia.org/wiki/Sunrise_equation to calc sunrise times
-            // by having user provide latitude
-            if moon || hour < 8 || hour > 18 {
-                moon_corners.draw(&mut buffer, BLACK, Some(WHITE));
-                full_moon.draw(&mut buffer, MOONDARK, None);
-                moon_phase.draw(&mut buffer, MOON, None);
-            } else if sunmoon {
-                sun_bg.draw(&mut buffer, SUNDARK, None);
-                sun.draw(&mut buffer, SUN, None);
+            clock.draw(&mut renderer, &mut buffer, &mut drawn, frame);
+            if terminal
+                .draw(&buffer, renderer.width(), renderer.height())
+                .is_err()
+            {
+                break;
             }
+            std::thread::sleep(Duration::from_millis(100));
         }
+        return;
+    }
 
-        if crazy > 0 {
-            let r = i;
-            for _ in r..(crazy + r) {
-                if up {
-                    if i + 1 == tails_frames.len() {
-                        up = false;
-                    } else {
-                        i = (i + 1).wrapping_rem(tails_frames.len());
-                    }
-                } else {
-                    if i == 0 {
-                        up = true;
-                    } else {
-                        i -= 1;
-                    }
-                }
-            }
-        } else {
-            if up {
-                if i + 1 == tails_frames.len() {
-                    up = false;
-                } else {
-                    i = (i + 1).wrapping_rem(tails_frames.len());
-                }
-            } else {
-                if i == 0 {
-                    up = true;
-                } else {
-                    i -= 1;
-                }
+    let mut window = open_window(renderer.width(), renderer.height(), borderless, resize);
+    set_background(&mut window, &renderer.config().theme);
+
+    let mut drawn = None;
+    while window.is_open() && !window.is_key_down(Key::Escape) && !window.is_key_down(Key::Q) {
+        if resize {
+            let (width, height) = window.get_size();
+            let fit = (width / CAT_WIDTH)
+                .min(height / CAT_HEIGHT)
+                .clamp(1, MAX_SCALE);
+            if fit != renderer.config().scale {
+                renderer = ClockRenderer::new(Config {
+                    scale: fit,
+                    ..*renderer.config()
+                });
+                buffer = vec![theme.background; renderer.width() * renderer.height()];
+                drawn = None;
             }
         }
+        for key in window.get_keys_pressed(KeyRepeat::No).unwrap_or_default() {
+            clock.press(match key {
+                Key::Space => ' ',
+                Key::R => 'r',
+                _ => '\0',
+            });
+        }
+        let frame = clock.tick();
+        let theme = with_phase(theme_at(&clock.time), clock.pomodoro.as_ref());
+        if theme != renderer.config().theme {
+            renderer.set_theme(theme);
+            set_background(&mut window, &theme);
+            drawn = None;
+        }
+        clock.draw(&mut renderer, &mut buffer, &mut drawn, frame);
+
         // We unwrap here as we want this code to exit if it fails. Real applications may want to handle this in a different way
         window
-            .update_with_buffer(&buffer, CAT_WIDTH, CAT_HEIGHT)
+            .update_with_buffer(&buffer, renderer.width(), renderer.height())
             .unwrap();
 
         let millis = std::time::Duration::from_millis(100);
@@ -860,83 +500,364 @@ fn main() {
     }
 }
 
-fn add_time_offset(
-    hour: &mut u8,
-    minutes: &mut u8,
-    offset_sign: bool,
-    offset_hour: usize,
-    offset_min: usize,
-) {
-    match (offset_sign, (offset_hour, offset_min)) {
-        (false, (h, m)) if h > 0 || m > 0 => {
-            let mut ih = *hour as i64;
-            ih -= h as i64;
-            let mut im = *minutes as i64;
-            im -= m as i64;
-            if im < 0 {
-                ih -= if (im.abs() % 59) > 0 { 1 } else { 0 };
-                ih %= 23;
-            }
-            if im < 0 {
-                *minutes = (60 + im) as u8;
-            } else {
-                *minutes = im as u8;
+/// Keeps the displayed time and the tail swing going, one call per frame.
+struct Clock {
+    timekeeper: Timekeeper,
+    time: ClockTime,
+    frame: usize,
+    up: bool,
+    crazy: usize,
+    /// Seconds the hands of `--crazy` have run ahead of the time.
+    hands_offset: i64,
+    alarms: alarm::AlarmClock<audio::Player>,
+    chime: Option<Chime>,
+    /// Command of `--chime-command`, run when the hour strikes.
+    chime_command: Option<String>,
+    /// Timer of `--timer` or `--stopwatch`, shown instead of the time.
+    timer: Option<Timer>,
+    pomodoro: Option<Pomodoro>,
+    /// Where ended Pomodoro phases are logged.
+    pomodoro_log: Option<std::path::PathBuf>,
+    blinker: Blinker,
+    lids: Lids,
+}
+
+impl Clock {
+    /// Reads the time, rings any alarm due, strikes the hour, blinks, and returns the tail/eyes
+    /// frame to draw.
+    fn tick(&mut self) -> usize {
+        let crazy = self.crazy;
+        if crazy > 0 {
+            /* a minute, a second and six more seconds for each --crazy per frame */
+            self.hands_offset += 60 + 1 + 6 * crazy as i64;
+        }
+        self.time = self.timekeeper.now();
+        if let Err(err) = self.alarms.update(&self.time) {
+            eprintln!("WARNING: Could not play alarm sound {}", err);
+        }
+        if let Some(strikes) = self
+            .chime
+            .as_mut()
+            .and_then(|chime| chime.update(&self.time))
+        {
+            if let Some(command) = &self.chime_command {
+                run_chime_command(command, self.time.hour, strikes);
             }
-            if ih < 0 {
-                *hour = (24 + ih) as u8;
-            } else {
-                *hour = ih as u8;
+        }
+        if let Some(session) = self.pomodoro.as_mut().and_then(Pomodoro::update) {
+            if let Some(path) = &self.pomodoro_log {
+                if let Err(err) = session.append_to_log(path) {
+                    eprintln!(
+                        "WARNING: Could not log Pomodoro session to {}: {}",
+                        path.display(),
+                        err
+                    );
+                }
             }
         }
-        (true, (h, m)) if h > 0 || m > 0 => {
-            *hour += h as u8;
-            *hour %= 24;
-            *minutes += m as u8;
-            if *minutes >= 60 {
-                *hour += *minutes / 60;
-                *hour %= 24;
+
+        self.lids = self.blinker.next(blink::is_late(&self.time));
+
+        let frame = self.frame;
+        /* the cat swings its tail, or rolls its eyes, like crazy when startled */
+        let speed = match self.reaction() {
+            Reaction::Calm | Reaction::Chiming => crazy.max(1),
+            Reaction::Alarmed | Reaction::TimeUp => crazy.max(RINGING_SPEED),
+        };
+        for _ in 0..speed {
+            let previous = self.frame;
+            (self.frame, self.up) = next_frame(self.frame, self.up);
+            /* the hour strikes as the tail leaves a side */
+            if (previous == 0 || previous == NUM_TAILS - 1) && self.frame != previous {
+                if let Some(chime) = &mut self.chime {
+                    chime.strike();
+                }
             }
-            *minutes %= 60;
         }
-        _ => {}
+        frame
+    }
+
+    fn reaction(&self) -> Reaction {
+        if self.alarms.ringing().is_some() {
+            Reaction::Alarmed
+        } else if self.timer.as_ref().is_some_and(Timer::is_finished) {
+            Reaction::TimeUp
+        } else if self.chime.as_ref().is_some_and(Chime::is_striking) {
+            Reaction::Chiming
+        } else {
+            Reaction::Calm
+        }
+    }
+
+    /// Handles a key press: any key stops a ringing alarm, otherwise space starts and pauses
+    /// the timer or Pomodoro phase and `r` resets it.
+    fn press(&mut self, key: char) {
+        if self.alarms.ringing().is_some() {
+            self.alarms.dismiss();
+            return;
+        }
+        match (&mut self.timer, &mut self.pomodoro, key) {
+            (Some(timer), _, ' ') => timer.toggle(),
+            (Some(timer), _, 'r') => timer.reset(),
+            (_, Some(pomodoro), ' ') => pomodoro.toggle(),
+            (_, Some(pomodoro), 'r') => pomodoro.restart(),
+            _ => {}
+        }
+    }
+
+    /// Draws the clock into `buffer`, only redrawing what changed if it has the time `drawn`.
+    fn draw(
+        &self,
+        renderer: &mut ClockRenderer,
+        buffer: &mut [u32],
+        drawn: &mut Option<ClockTime>,
+        frame: usize,
+    ) {
+        renderer.set_reaction(self.reaction());
+        renderer.set_lids(self.lids);
+        renderer.set_hands_offset(self.hands_offset);
+        renderer.show_duration(
+            self.timer
+                .as_ref()
+                .or(self.pomodoro.as_ref().map(Pomodoro::timer))
+                .map(Timer::shown),
+        );
+        renderer.set_progress(self.pomodoro.as_ref().map(Pomodoro::progress));
+        match drawn {
+            Some(previous) => renderer.redraw_into(buffer, previous, &self.time, frame),
+            None => renderer.render_into(buffer, &self.time, frame),
+        }
+        *drawn = Some(self.time);
     }
 }
 
-#[test]
-fn test_time_offset() {
-    let mut hour: u8 = 14;
-    let mut minutes: u8 = 32;
+/// Runs `command` of `--chime-command` with the shell in the background, with the hour and its
+/// number of strikes in `KITKAT_HOUR` and `KITKAT_STRIKES`.
+fn run_chime_command(command: &str, hour: u8, strikes: u32) {
+    let mut shell = if cfg!(target_os = "windows") {
+        std::process::Command::new("cmd")
+    } else {
+        std::process::Command::new("sh")
+    };
+    shell.arg(if cfg!(target_os = "windows") {
+        "/C"
+    } else {
+        "-c"
+    });
+    match shell
+        .arg(command)
+        .env("KITKAT_HOUR", hour.to_string())
+        .env("KITKAT_STRIKES", strikes.to_string())
+        .stdin(std::process::Stdio::null())
+        .spawn()
+    {
+        Ok(mut child) => {
+            /* reap it when it is done */
+            std::thread::spawn(move || child.wait());
+        }
+        Err(err) => eprintln!(
+            "WARNING: Could not run chime command `{}`: {}",
+            command, err
+        ),
+    }
+}
 
-    add_time_offset(&mut hour, &mut minutes, true, 0, 0);
-    assert_eq!((hour, minutes), (14, 32));
+/// `theme` with the tie coloured for the phase of `pomodoro`, if there is one.
+fn with_phase(theme: Theme, pomodoro: Option<&Pomodoro>) -> Theme {
+    match pomodoro {
+        Some(pomodoro) => theme.with_phase(pomodoro.phase()),
+        None => theme,
+    }
+}
+
+/// Where `--pomodoro` logs without `--pomodoro-log`: `kitkat/pomodoro.log` in the user's data
+/// directory.
+fn default_pomodoro_log() -> Option<std::path::PathBuf> {
+    let data = std::env::var_os("XDG_DATA_HOME")
+        .map(std::path::PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".local/share"))
+        })
+        .or_else(|| std::env::var_os("APPDATA").map(std::path::PathBuf::from))?;
+    Some(data.join("kitkat").join("pomodoro.log"))
+}
+
+fn open_window(width: usize, height: usize, borderless: bool, resize: bool) -> Window {
+    let mut window = Window::new(
+        "kitkat - ESC or q to exit",
+        width,
+        height,
+        WindowOptions {
+            title: true,
+            borderless,
+            resize,
+            // We scale the clock ourselves to fit the window, don't let minifb blur it.
+            scale_mode: ScaleMode::Center,
+            transparency: false,
+            ..WindowOptions::default()
+        },
+    )
+    .unwrap_or_else(|e| {
+        panic!("{}", e);
+    });
+
+    // Limit to max ~60 fps update rate
+    window.limit_update_rate(Some(std::time::Duration::from_micros(16600)));
+    window
+}
 
-    add_time_offset(&mut hour, &mut minutes, false, 0, 0);
-    assert_eq!((hour, minutes), (14, 32));
+fn set_background(window: &mut Window, theme: &Theme) {
+    let [_, r, g, b] = theme.background.to_be_bytes();
+    window.set_background_color(r as usize, g as usize, b as usize);
+}
 
-    add_time_offset(&mut hour, &mut minutes, true, 2, 0);
-    assert_eq!((hour, minutes), (16, 32));
+fn write_snapshot(path: &str, buffer: &[u32], width: usize, height: usize) {
+    if let Err(err) = std::fs::File::create(path)
+        .and_then(|f| png::write_png(std::io::BufWriter::new(f), buffer, width, height))
+    {
+        eprintln!("ERROR: Could not write snapshot to {}: {}", path, err);
+        std::process::exit(1);
+    }
+}
 
-    hour = 14;
-    minutes = 32;
+/// Parses the `LABEL=ZONE,LABEL=ZONE,...` of `--clocks`.
+fn parse_clocks(spec: &str) -> Result<Vec<(String, tz::TimeZone)>, String> {
+    spec.split(',')
+        .map(|clock| {
+            let (label, zone) = clock.split_once('=').ok_or_else(|| {
+                format!(
+                    "--clocks requires LABEL=ZONE pairs, e.g. Athens=Europe/Athens, not `{}`",
+                    clock
+                )
+            })?;
+            Ok((label.trim().to_string(), tz::TimeZone::load(zone.trim())?))
+        })
+        .collect()
+}
 
-    add_time_offset(&mut hour, &mut minutes, true, 2, 32);
-    assert_eq!((hour, minutes), (17, 04));
+/// Collects the alarms of `--alarm`, with the sound of `--alarm-sound`, and of `--alarms`.
+fn parse_alarms(args: &[String]) -> Result<Vec<alarm::Alarm>, String> {
+    let sound = flag_value(args, "--alarm-sound", "FILE").map(std::path::PathBuf::from);
+    let mut alarms = vec![];
+    for (i, arg) in args.iter().enumerate() {
+        if arg == "--alarm" {
+            let mut alarm = args
+                .get(i + 1)
+                .ok_or_else(|| "--alarm requires a TIME argument".to_string())
+                .and_then(|time| {
+                    alarm::Alarm::parse(time).map_err(|err| format!("--alarm: {}", err))
+                })?;
+            alarm.sound = sound.clone();
+            alarms.push(alarm);
+        }
+    }
+    if let Some(path) = flag_value(args, "--alarms", "FILE") {
+        alarms.extend(alarm::Alarm::load(std::path::Path::new(path))?);
+    }
+    Ok(alarms)
+}
 
-    hour = 14;
-    minutes = 32;
+/// Shows a row of `clocks`, each in its own time zone, or writes a snapshot of them.
+fn run_wall(
+    config: Config,
+    clocks: &[(String, tz::TimeZone)],
+    snapshot: Option<&str>,
+    borderless: bool,
+    resize: bool,
+    theme_at: &dyn Fn(&ClockTime) -> Theme,
+) {
+    let captions = clocks
+        .iter()
+        .map(|(label, _)| label.as_str())
+        .collect::<Vec<&str>>();
+    let mut wall = match ClockWall::new(config, &captions) {
+        Ok(wall) => wall,
+        Err(err) => {
+            eprintln!("ERROR: {}", err);
+            return;
+        }
+    };
+    let times = || {
+        let (now, nanos) = SystemClock.now_precise();
+        clocks
+            .iter()
+            .map(|(_, zone)| ClockTime {
+                nanos,
+                ..ClockTime::in_zone(now, zone)
+            })
+            .collect::<Vec<ClockTime>>()
+    };
+    let mut buffer: Vec<u32> = vec![config.theme.background; wall.width() * wall.height()];
+    if let Some(path) = snapshot {
+        wall.render_into(&mut buffer, &times(), NUM_TAILS / 2);
+        write_snapshot(path, &buffer, wall.width(), wall.height());
+        return;
+    }
 
-    add_time_offset(&mut hour, &mut minutes, false, 2, 0);
-    assert_eq!((hour, minutes), (12, 32));
+    let mut window = open_window(wall.width(), wall.height(), borderless, resize);
+    set_background(&mut window, &wall.config().theme);
+    let (mut frame, mut up) = (0, true);
+    while window.is_open() && !window.is_key_down(Key::Escape) && !window.is_key_down(Key::Q) {
+        if resize {
+            let (width, height) = window.get_size();
+            let fit = (width / (wall.len() * CAT_WIDTH))
+                .min(height / (CAT_HEIGHT + CAPTION_HEIGHT))
+                .clamp(1, MAX_SCALE);
+            if fit != wall.config().scale {
+                wall.set_scale(fit);
+                buffer = vec![wall.config().theme.background; wall.width() * wall.height()];
+            }
+        }
+        let times = times();
+        /* the window has one theme, the first clock tells night from day */
+        let theme = theme_at(&times[0]);
+        if theme != wall.config().theme {
+            wall.set_theme(theme);
+            set_background(&mut window, &theme);
+        }
+        wall.render_into(&mut buffer, &times, frame);
 
-    hour = 14;
-    minutes = 32;
+        window
+            .update_with_buffer(&buffer, wall.width(), wall.height())
+            .unwrap();
+        (frame, up) = next_frame(frame, up);
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
 
-    add_time_offset(&mut hour, &mut minutes, false, 2, 33);
-    assert_eq!((hour, minutes), (11, 59));
+/// Returns the value following `flag`, if `flag` was given. Exits if the value is missing.
+fn flag_value<'a>(args: &'a [String], flag: &str, value_name: &str) -> Option<&'a str> {
+    let pos = args.iter().position(|s| s == flag)?;
+    match args.get(pos + 1) {
+        Some(value) => Some(value.as_str()),
+        None => {
+            eprintln!("{} requires a {} argument", flag, value_name);
+            std::process::exit(1);
+        }
+    }
+}
 
-    hour = 14;
-    minutes = 32;
+/// Parses `HH:MM:SS` or `HH:MM` into hour, minutes and seconds.
+fn parse_hms(s: &str) -> Option<(u8, u8, u8)> {
+    let mut parts = s.split(':');
+    let hour = parts.next()?.parse::<u8>().ok().filter(|h| *h < 24)?;
+    let minutes = parts.next()?.parse::<u8>().ok().filter(|m| *m < 60)?;
+    let seconds = match parts.next() {
+        Some(seconds) => seconds.parse::<u8>().ok().filter(|s| *s < 60)?,
+        None => 0,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((hour, minutes, seconds))
+}
 
-    add_time_offset(&mut hour, &mut minutes, true, 24, 59);
-    assert_eq!((hour, minutes), (15, 31));
+#[test]
+fn test_parse_hms() {
+    assert_eq!(parse_hms("10:08:42"), Some((10, 8, 42)));
+    assert_eq!(parse_hms("23:59"), Some((23, 59, 0)));
+    assert_eq!(parse_hms("24:00:00"), None);
+    assert_eq!(parse_hms("12:60"), None);
+    assert_eq!(parse_hms("12:00:00:00"), None);
+    assert_eq!(parse_hms("noon"), None);
 }
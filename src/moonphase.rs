@@ -21,8 +21,6 @@
 
 use super::*;
 
-const _2001_01_01: u64 = 978300000;
-
 #[repr(u8)]
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub enum MoonPosition {
@@ -44,6 +42,27 @@ pub enum MoonPosition {
     WaningCrescent = 7,
 }
 
+/// Which way up the moon is seen from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Hemisphere {
+    /// Waxing moons are lit on the right, as in the [`MoonPosition`] emoji.
+    #[default]
+    Northern,
+    /// The moon is upside down: waxing moons are lit on the left.
+    Southern,
+}
+
+impl Hemisphere {
+    /// The hemisphere of `latitude` in degrees, the equator is counted as northern.
+    pub fn from_latitude(latitude: f64) -> Self {
+        if latitude < 0.0 {
+            Hemisphere::Southern
+        } else {
+            Hemisphere::Northern
+        }
+    }
+}
+
 pub fn corner_fill() -> Image {
     let mut ret = Image::new(
         MOON_WIDTH,
@@ -152,43 +171,285 @@ impl From<MoonPosition> for Image {
     }
 }
 
-pub fn position(now: Option<u64>) -> f64 {
-    let now: u64 = now.unwrap_or(
+/*
+ * Difference between dynamical and universal time in seconds, from the polynomial fits of
+ * Espenak and Meeus, with their long term parabola outside of 1961-2050.
+ */
+fn delta_t(julian_date: f64) -> f64 {
+    let year = 2000.0 + (julian_date - 2451544.5) / 365.25;
+    if (2005.0..2050.0).contains(&year) {
+        let t = year - 2000.0;
+        62.92 + 0.32217 * t + 0.005589 * t * t
+    } else if (1986.0..2005.0).contains(&year) {
+        let t = year - 2000.0;
+        63.86 + 0.3345 * t - 0.060374 * t.powi(2)
+            + 0.0017275 * t.powi(3)
+            + 0.000651814 * t.powi(4)
+            + 0.00002373599 * t.powi(5)
+    } else if (1961.0..1986.0).contains(&year) {
+        let t = year - 1975.0;
+        45.45 + 1.067 * t - t.powi(2) / 260.0 - t.powi(3) / 718.0
+    } else {
+        let u = (year - 1820.0) / 100.0;
+        -20.0 + 32.0 * u * u
+    }
+}
+
+/// UNIX timestamp of a principal phase of the moon, counted in quarter lunations from the new
+/// moon of 2000-01-06: `4 * n` are new moons, `4 * n + 1` first quarters, `4 * n + 2` full moons
+/// and `4 * n + 3` last quarters.
+///
+/// This is the algorithm of chapter 49 of Jean Meeus' Astronomical Algorithms, good to a
+/// minute or so.
+pub fn principal_phase(quarter: i64) -> i64 {
+    let sin = |deg: f64| deg.to_radians().sin();
+    let cos = |deg: f64| deg.to_radians().cos();
+
+    let k = quarter as f64 / 4.0;
+    let t = k / 1236.85;
+    /* Mean phase, in Julian Ephemeris Days */
+    let mut jde = 2451550.09766 + 29.530588861 * k + 0.00015437 * t.powi(2)
+        - 0.000000150 * t.powi(3)
+        + 0.00000000073 * t.powi(4);
+    /* Eccentricity of Earth's orbit */
+    let e = 1.0 - 0.002516 * t - 0.0000074 * t.powi(2);
+    /* Sun's mean anomaly */
+    let m = 2.5534 + 29.10535670 * k - 0.0000014 * t.powi(2) - 0.00000011 * t.powi(3);
+    /* Moon's mean anomaly */
+    let mp = 201.5643 + 385.81693528 * k + 0.0107582 * t.powi(2) + 0.00001238 * t.powi(3)
+        - 0.000000058 * t.powi(4);
+    /* Moon's argument of latitude */
+    let f = 160.7108 + 390.67050284 * k - 0.0016118 * t.powi(2) - 0.00000227 * t.powi(3)
+        + 0.000000011 * t.powi(4);
+    /* Longitude of the ascending node of the lunar orbit */
+    let omega = 124.7746 - 1.56375588 * k + 0.0020672 * t.powi(2) + 0.00000215 * t.powi(3);
+
+    jde += match quarter.rem_euclid(4) {
+        /* new and full moon only differ in the first few terms */
+        q @ (0 | 2) => {
+            let c = if q == 0 {
+                [
+                    -0.40720, 0.17241, 0.01608, 0.01039, 0.00739, -0.00514, 0.00208,
+                ]
+            } else {
+                [
+                    -0.40614, 0.17302, 0.01614, 0.01043, 0.00734, -0.00515, 0.00209,
+                ]
+            };
+            c[0] * sin(mp)
+                + c[1] * e * sin(m)
+                + c[2] * sin(2.0 * mp)
+                + c[3] * sin(2.0 * f)
+                + c[4] * e * sin(mp - m)
+                + c[5] * e * sin(mp + m)
+                + c[6] * e * e * sin(2.0 * m)
+                - 0.00111 * sin(mp - 2.0 * f)
+                - 0.00057 * sin(mp + 2.0 * f)
+                + 0.00056 * e * sin(2.0 * mp + m)
+                - 0.00042 * sin(3.0 * mp)
+                + 0.00042 * e * sin(m + 2.0 * f)
+                + 0.00038 * e * sin(m - 2.0 * f)
+                - 0.00024 * e * sin(2.0 * mp - m)
+                - 0.00017 * sin(omega)
+                - 0.00007 * sin(mp + 2.0 * m)
+                + 0.00004 * sin(2.0 * mp - 2.0 * f)
+                + 0.00004 * sin(3.0 * m)
+                + 0.00003 * sin(mp + m - 2.0 * f)
+                + 0.00003 * sin(2.0 * mp + 2.0 * f)
+                - 0.00003 * sin(mp + m + 2.0 * f)
+                + 0.00003 * sin(mp - m + 2.0 * f)
+                - 0.00002 * sin(mp - m - 2.0 * f)
+                - 0.00002 * sin(3.0 * mp + m)
+                + 0.00002 * sin(4.0 * mp)
+        }
+        q => {
+            let w = 0.00306 - 0.00038 * e * cos(m) + 0.00026 * cos(mp) - 0.00002 * cos(mp - m)
+                + 0.00002 * cos(mp + m)
+                + 0.00002 * cos(2.0 * f);
+            -0.62801 * sin(mp) + 0.17172 * e * sin(m) - 0.01183 * e * sin(mp + m)
+                + 0.00862 * sin(2.0 * mp)
+                + 0.00804 * sin(2.0 * f)
+                + 0.00454 * e * sin(mp - m)
+                + 0.00204 * e * e * sin(2.0 * m)
+                - 0.00180 * sin(mp - 2.0 * f)
+                - 0.00070 * sin(mp + 2.0 * f)
+                - 0.00040 * sin(3.0 * mp)
+                - 0.00034 * e * sin(2.0 * mp - m)
+                + 0.00032 * e * sin(m + 2.0 * f)
+                + 0.00032 * e * sin(m - 2.0 * f)
+                - 0.00028 * e * e * sin(mp + 2.0 * m)
+                + 0.00027 * e * sin(2.0 * mp + m)
+                - 0.00017 * sin(omega)
+                - 0.00005 * sin(mp - m - 2.0 * f)
+                + 0.00004 * sin(2.0 * mp + 2.0 * f)
+                - 0.00004 * sin(mp + m + 2.0 * f)
+                + 0.00004 * sin(mp - 2.0 * m)
+                + 0.00003 * sin(mp + m - 2.0 * f)
+                + 0.00003 * sin(3.0 * m)
+                + 0.00002 * sin(2.0 * mp - 2.0 * f)
+                + 0.00002 * sin(mp - m + 2.0 * f)
+                - 0.00002 * sin(3.0 * mp + m)
+                + if q == 1 { w } else { -w }
+        }
+    };
+
+    /* Planetary arguments */
+    const PLANETARY: [(f64, f64, f64); 14] = [
+        (0.000325, 299.77, 0.107408),
+        (0.000165, 251.88, 0.016321),
+        (0.000164, 251.83, 26.651886),
+        (0.000126, 349.42, 36.412478),
+        (0.000110, 84.66, 18.206239),
+        (0.000062, 141.74, 53.303771),
+        (0.000060, 207.14, 2.453732),
+        (0.000056, 154.84, 7.306860),
+        (0.000047, 34.52, 27.261239),
+        (0.000042, 207.19, 0.121824),
+        (0.000040, 291.34, 1.844379),
+        (0.000037, 161.72, 24.198154),
+        (0.000035, 239.56, 25.513099),
+        (0.000023, 331.55, 3.592518),
+    ];
+    for (i, (coefficient, a, b)) in PLANETARY.iter().enumerate() {
+        let mut argument = a + b * k;
+        if i == 0 {
+            argument -= 0.009173 * t.powi(2);
+        }
+        jde += coefficient * sin(argument);
+    }
+
+    solar::timestamp(jde) - delta_t(jde).round() as i64
+}
+
+/// Fraction of the lunation at UNIX timestamp `now`, or the current time: `0.0` is new moon,
+/// `0.25` first quarter, `0.5` full moon and `0.75` last quarter, exactly at the instants of
+/// [`principal_phase`] and interpolated in between.
+///
+/// Timestamps before 1970 are negative; the phases are good for a few millennia either way.
+pub fn position(now: Option<i64>) -> f64 {
+    let now: i64 = now.unwrap_or_else(|| {
         std::time::SystemTime::now()
             .duration_since(std::time::SystemTime::UNIX_EPOCH)
             .unwrap_or_default()
-            .as_secs(),
-    );
+            .as_secs() as i64
+    });
+
+    /* Mean lunations since the new moon of 2000-01-06 */
+    let lunations = (solar::julian_date(now) - 2451550.09766) / 29.530588861;
+    let mut quarter = (lunations * 4.0).floor() as i64;
+    while principal_phase(quarter) > now {
+        quarter -= 1;
+    }
+    while principal_phase(quarter + 1) <= now {
+        quarter += 1;
+    }
+    let (start, end) = (principal_phase(quarter), principal_phase(quarter + 1));
+    (quarter.rem_euclid(4) as f64 + (now - start) as f64 / (end - start) as f64) / 4.0
+}
 
-    let diff = now - _2001_01_01;
-    let days = diff / 86400;
-    let lunations: f64 = 0.20439731 + ((days as f64) * 0.03386319269);
-    lunations % 1.0
+/// Fraction of the moon's disc that is lit at lunation `pos`, from `0.0` at new moon to `1.0` at
+/// full moon.
+pub fn illumination(pos: f64) -> f64 {
+    (1.0 - f64::cos(2.0 * PI * pos)) / 2.0
 }
 
-pub fn phase(pos: f64) -> MoonPosition {
-    let idx = (pos * 8.) + 0.5;
-    let idx = (idx as usize) & 7;
-    match idx {
-        0 => MoonPosition::NewMoon,
-        1 => MoonPosition::WaxingCrescent,
-        2 => MoonPosition::FirstQuarter,
-        3 => MoonPosition::WaxingGibbous,
-        4 => MoonPosition::FullMoon,
-        5 => MoonPosition::WaningGibbous,
-        6 => MoonPosition::LastQuarter,
-        7 => MoonPosition::WaningCrescent,
-        _ => unreachable!(),
+/* Radius of the moon's disc drawn by MoonPosition::FullMoon, to the pixel edges */
+const MOON_RADIUS: f64 = 6.5;
+
+/*
+ * Whether point (x, y), relative to the center of the moon, is on its lit side at lunation
+ * pos. The terminator is half an ellipse, as wide as the disc times cos(2 PI pos).
+ */
+fn is_lit(pos: f64, (x, y): (f64, f64)) -> bool {
+    let half_width2 = MOON_RADIUS * MOON_RADIUS - y * y;
+    if x * x > half_width2 {
+        return false;
+    }
+    let terminator = f64::sqrt(half_width2) * f64::cos(2.0 * PI * pos);
+    if pos < 0.5 {
+        /* waxing, lit from the right */
+        x > terminator
+    } else {
+        x < -terminator
     }
 }
 
-#[test]
-fn test_position() {
-    assert_eq!(position(Some(1637831210)), 0.6821471127699965);
-    assert_eq!(
-        phase(position(Some(1637831210))),
-        MoonPosition::WaningGibbous
+/// The lit part of the moon at any lunation `pos` as seen from `hemisphere`, unlike the eight
+/// [`MoonPosition`]s. Pixels on the terminator and the limb are partly covered.
+pub fn lit(pos: f64, hemisphere: Hemisphere) -> Image {
+    /* sub-pixel samples per side */
+    const SAMPLES: usize = 4;
+    let mut ret = Image::new(
+        MOON_WIDTH,
+        MOON_WIDTH,
+        CAT_WIDTH / 2 + CAT_WIDTH / 5 + 2,
+        TAIL.y_offset - 4 * MOON_WIDTH,
+    );
+    let center = (MOON_WIDTH / 2) as f64;
+    for y in 0..MOON_WIDTH {
+        for x in 0..MOON_WIDTH {
+            let mut lit_samples = 0;
+            for sy in 0..SAMPLES {
+                for sx in 0..SAMPLES {
+                    let mut dx = x as f64 - center + (sx as f64 + 0.5) / SAMPLES as f64 - 0.5;
+                    let dy = y as f64 - center + (sy as f64 + 0.5) / SAMPLES as f64 - 0.5;
+                    if hemisphere == Hemisphere::Southern {
+                        dx = -dx;
+                    }
+                    if is_lit(pos, (dx, dy)) {
+                        lit_samples += 1;
+                    }
+                }
+            }
+            ret.plot_aa(
+                x as i64,
+                y as i64,
+                lit_samples as f64 / (SAMPLES * SAMPLES) as f64,
+            );
+        }
+    }
+    ret
+}
+
+/// The lit percentage of the moon at lunation `pos`, written under it.
+pub fn illumination_label(pos: f64) -> Image {
+    let percent = (illumination(pos) * 100.0).round() as u32;
+    let mut glyphs = percent
+        .to_string()
+        .bytes()
+        .map(|b| date::digit(b - b'0'))
+        .collect::<Vec<Image>>();
+    /* % sign */
+    let mut sign = Image::new(5, 7, 0, 0);
+    for (x, y) in [
+        (0, 0),
+        (1, 0),
+        (0, 1),
+        (1, 1),
+        (3, 5),
+        (4, 5),
+        (3, 6),
+        (4, 6),
+    ] {
+        sign.plot(x, y);
+    }
+    sign.plot_line_width((4, 1), (0, 5), 0.0);
+    glyphs.push(sign);
+
+    let width = glyphs.iter().map(|g| g.width + 1).sum::<usize>() - 1;
+    let height = glyphs.iter().map(|g| g.height).max().unwrap_or(0);
+    let mut ret = Image::new(
+        width,
+        height,
+        CAT_WIDTH / 2 + CAT_WIDTH / 5 + 2 + MOON_WIDTH / 2 - width / 2,
+        TAIL.y_offset - 3 * MOON_WIDTH + 2,
     );
+    let mut x = 0;
+    for glyph in glyphs {
+        ret.copy(&glyph, x, 0);
+        x += glyph.width + 1;
+    }
+    ret
 }
 
 pub fn sun() -> Image {
@@ -231,3 +492,137 @@ pub fn sun_background() -> Image {
     ret.flood_fill(CENTER.0, CENTER.1);
     ret
 }
+
+/// The [`sun`] and [`sun_background`] during twilight, sunk under the horizon by `depth`: from
+/// `0.0`, when the whole disc is above it, to `1.0`, when all but the horizon is gone.
+pub fn sinking_sun(depth: f64) -> (Image, Image) {
+    let (mut sun, mut background) = (sun(), sun_background());
+    /* the disc spans rows 2 to 14 */
+    let horizon = MOON_WIDTH / 2 + 6 - (depth.clamp(0.0, 1.0) * 12.0).round() as usize;
+    for image in [&mut sun, &mut background] {
+        for byte in image.bytes[horizon * MOON_WIDTH..].iter_mut() {
+            *byte = WHITE;
+        }
+    }
+    sun.plot_line_width(
+        (1, horizon as i64),
+        (MOON_WIDTH as i64 - 1, horizon as i64),
+        0.0,
+    );
+    (sun, background)
+}
+
+#[test]
+fn test_position() {
+    /* 2021-11-25 09:06 UTC, between the full moon of the 19th and last quarter of the 27th */
+    let pos = position(Some(1637831210));
+    assert!((0.683..0.686).contains(&pos), "{}", pos);
+
+    /* Phases from the almanacs, to the minute */
+    for (quarter, timestamp) in [
+        /* new moon, 1977-02-18 03:37:42 TD (03:36:54 UT), example 49.a of Astronomical Algorithms */
+        (-283 * 4, 225085014),
+        /* new moon, 2000-01-06 18:14 UTC */
+        (0, 947182440),
+        /* solar eclipse new moon, 2017-08-21 18:30 UTC */
+        (218 * 4, 1503340200),
+        /* first quarter, 2021-11-11 12:46 UTC */
+        (270 * 4 + 1, 1636634760),
+        /* lunar eclipse full moon, 2021-11-19 08:57 UTC */
+        (270 * 4 + 2, 1637312220),
+        /* last quarter, 2021-11-27 12:28 UTC */
+        (270 * 4 + 3, 1638016080),
+    ] {
+        let computed = principal_phase(quarter);
+        assert!(
+            (computed - timestamp).abs() < 90,
+            "phase {}: {} != {}",
+            quarter,
+            computed,
+            timestamp
+        );
+        let (pos, expected) = (
+            position(Some(computed + 1)),
+            quarter.rem_euclid(4) as f64 / 4.0,
+        );
+        assert!(
+            (pos - expected).abs() < 1e-4,
+            "phase {}: {} != {}",
+            quarter,
+            pos,
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_position_range() {
+    /* 1970-01-01, a week before the new moon of 1970-01-07 20:35 UTC */
+    let pos = position(Some(0));
+    assert!((0.75..1.0).contains(&pos), "{}", pos);
+    for (timestamp, phase) in [
+        /* new moon, 1970-01-07 20:35 UTC */
+        (592500, 0.0),
+        /* full moon, 1970-01-22 12:55 UTC */
+        (1860900, 0.5),
+        /* full moon, 1999-01-31 16:07 UTC */
+        (917798820, 0.5),
+        /* solar eclipse new moon, 1999-08-11 11:08 UTC */
+        (934369680, 0.0),
+    ] {
+        let pos = position(Some(timestamp));
+        /* a minute is about 6e-6 of a lunation */
+        let off = (pos - phase + 0.5).rem_euclid(1.0) - 0.5;
+        assert!(off.abs() < 2e-5, "{}: {} != {}", timestamp, pos, phase);
+    }
+
+    /* before the epoch, and a century on: phases come in order, a quarter lunation apart */
+    for timestamp in [-2208988800, -86400, 4102444800] {
+        let pos = position(Some(timestamp));
+        assert!((0.0..1.0).contains(&pos));
+        let start = ((solar::julian_date(timestamp) - 2451550.09766) / 29.530588861 * 4.0) as i64;
+        for quarter in start..start + 8 {
+            let days = (principal_phase(quarter + 1) - principal_phase(quarter)) as f64 / 86400.0;
+            assert!((6.0..9.0).contains(&days), "{}", days);
+        }
+        assert!((position(Some(timestamp + 29 * 86400 + 12 * 3600 + 44 * 60)) - pos).abs() < 0.03);
+    }
+}
+
+#[test]
+fn test_lit() {
+    let lit_area = |pos: f64| {
+        lit(pos, Hemisphere::Northern)
+            .bytes
+            .iter()
+            .map(|p| coverage(*p))
+            .sum::<f64>()
+    };
+    let disc = PI * MOON_RADIUS * MOON_RADIUS;
+    assert_eq!(lit_area(0.0), 0.0);
+    for pos in [0.1, 0.25, 0.4, 0.5, 0.6, 0.75, 0.9] {
+        assert!((lit_area(pos) - illumination(pos) * disc).abs() < 1.0);
+    }
+    assert!(lit_area(0.31) > lit_area(0.3));
+    /* waxing is lit on the right, waning on the left */
+    let quarter = lit(0.25, Hemisphere::Northern);
+    assert_eq!(quarter.get(12, 8), Some(BLACK));
+    assert_eq!(quarter.get(4, 8), Some(WHITE));
+    let quarter = lit(0.75, Hemisphere::Northern);
+    assert_eq!(quarter.get(12, 8), Some(WHITE));
+    assert_eq!(quarter.get(4, 8), Some(BLACK));
+
+    /* and the other way around, seen upside down */
+    assert_eq!(
+        lit(0.25, Hemisphere::Southern).bytes,
+        lit(0.75, Hemisphere::Northern).bytes
+    );
+    let crescent = lit(0.125, Hemisphere::Southern);
+    assert_eq!(crescent.get(13, 8), Some(WHITE));
+    assert_eq!(crescent.get(3, 8), Some(BLACK));
+    assert_eq!(
+        lit(0.5, Hemisphere::Southern).bytes,
+        lit(0.5, Hemisphere::Northern).bytes
+    );
+    assert_eq!(Hemisphere::from_latitude(-33.87), Hemisphere::Southern);
+}
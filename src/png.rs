@@ -0,0 +1,126 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::io::{self, Write};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+/* Maximum length of a stored deflate block */
+const MAX_STORED_BLOCK: usize = 0xffff;
+
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc: u32 = 0xffff_ffff;
+    for b in bytes {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 > 0 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+pub fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b): (u32, u32) = (1, 0);
+    for byte in bytes {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn write_chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    let mut crc_input = Vec::with_capacity(4 + data.len());
+    crc_input.extend_from_slice(kind);
+    crc_input.extend_from_slice(data);
+    w.write_all(&crc_input)?;
+    w.write_all(&crc32(&crc_input).to_be_bytes())
+}
+
+/// Wraps `data` in a zlib stream made of stored (uncompressed) deflate blocks.
+pub fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut ret = Vec::with_capacity(data.len() + 6 + 5 * (data.len() / MAX_STORED_BLOCK + 1));
+    /* CMF: deflate with 32K window, FLG: no dictionary, check bits */
+    ret.extend_from_slice(&[0x78, 0x01]);
+    let mut chunks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if chunks.peek().is_none() {
+        ret.extend_from_slice(&[0x01, 0x00, 0x00, 0xff, 0xff]);
+    }
+    while let Some(chunk) = chunks.next() {
+        ret.push(if chunks.peek().is_none() { 0x01 } else { 0x00 });
+        let len = chunk.len() as u16;
+        ret.extend_from_slice(&len.to_le_bytes());
+        ret.extend_from_slice(&(!len).to_le_bytes());
+        ret.extend_from_slice(chunk);
+    }
+    ret.extend_from_slice(&adler32(data).to_be_bytes());
+    ret
+}
+
+/// Writes a `width x height` framebuffer of `0RGB` pixels as an 8-bit RGB PNG image.
+///
+/// The image data is not compressed, which is fine for the small sizes we deal with.
+pub fn write_png<W: Write>(
+    mut w: W,
+    buffer: &[u32],
+    width: usize,
+    height: usize,
+) -> io::Result<()> {
+    debug_assert_eq!(buffer.len(), width * height);
+    w.write_all(PNG_SIGNATURE)?;
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&(width as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(height as u32).to_be_bytes());
+    /* bit depth 8, colour type 2 (RGB), deflate, adaptive filtering, no interlace */
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+    write_chunk(&mut w, b"IHDR", &ihdr)?;
+
+    let mut raw = Vec::with_capacity(height * (1 + 3 * width));
+    for row in buffer.chunks(width) {
+        /* filter type: none */
+        raw.push(0);
+        for pixel in row {
+            raw.extend_from_slice(&pixel.to_be_bytes()[1..]);
+        }
+    }
+    write_chunk(&mut w, b"IDAT", &zlib_stored(&raw))?;
+    write_chunk(&mut w, b"IEND", &[])?;
+    w.flush()
+}
+
+#[test]
+fn test_png() {
+    assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+
+    let mut out = vec![];
+    write_png(&mut out, &[0xffffff, 0, 0x007fff, 0xffeb3b], 2, 2).unwrap();
+    assert!(out.starts_with(PNG_SIGNATURE));
+    assert!(out.ends_with(b"IEND\xae\x42\x60\x82"));
+    let data = zlib_stored(&[
+        0, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0, 0x7f, 0xff, 0xff, 0xeb, 0x3b,
+    ]);
+    assert!(out.windows(data.len()).any(|w| w == data.as_slice()));
+}
@@ -0,0 +1,313 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Pomodoro sessions: work, then a short break, and a long break after every few work
+//! sessions.
+
+use crate::timer::{parse_duration, Mode, Timer};
+use crate::{tz, SystemClock, WallClock};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    /// Its name in the session log.
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Work => "work",
+            Phase::ShortBreak => "short-break",
+            Phase::LongBreak => "long-break",
+        }
+    }
+}
+
+/// How long the phases last.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Settings {
+    pub work: Duration,
+    pub short_break: Duration,
+    pub long_break: Duration,
+    /// Work sessions before a long break.
+    pub sessions: u32,
+}
+
+impl Default for Settings {
+    /// 25 minutes of work and 5 minutes of break, with 15 minutes after every 4th session.
+    fn default() -> Self {
+        Settings {
+            work: Duration::from_secs(25 * 60),
+            short_break: Duration::from_secs(5 * 60),
+            long_break: Duration::from_secs(15 * 60),
+            sessions: 4,
+        }
+    }
+}
+
+impl Settings {
+    /// Parses `WORK,SHORT,LONG,SESSIONS`, e.g. `50m,10m,30m,3`. Settings left out at the end
+    /// keep their default.
+    pub fn parse(s: &str) -> Option<Settings> {
+        let mut settings = Settings::default();
+        let fields = s.split(',').map(str::trim).collect::<Vec<&str>>();
+        if fields.len() > 4 {
+            return None;
+        }
+        let lengths = [
+            &mut settings.work,
+            &mut settings.short_break,
+            &mut settings.long_break,
+        ];
+        for (length, field) in lengths.into_iter().zip(&fields) {
+            *length = parse_duration(field).filter(|d| !d.is_zero())?;
+        }
+        if let Some(field) = fields.get(3) {
+            settings.sessions = field.parse().ok().filter(|n| *n > 0)?;
+        }
+        Some(settings)
+    }
+
+    pub fn duration(&self, phase: Phase) -> Duration {
+        match phase {
+            Phase::Work => self.work,
+            Phase::ShortBreak => self.short_break,
+            Phase::LongBreak => self.long_break,
+        }
+    }
+}
+
+/// A phase that ran to its end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Session {
+    pub phase: Phase,
+    /// When it started, as a UNIX timestamp.
+    pub start: i64,
+    /// When it ended, as a UNIX timestamp.
+    pub end: i64,
+}
+
+/* A UNIX timestamp as an RFC 3339 UTC date and time, e.g. 2021-11-25T09:06:50Z. */
+fn utc(timestamp: i64) -> String {
+    let (year, month, day) = tz::civil_from_days(timestamp.div_euclid(86400));
+    let seconds = timestamp.rem_euclid(86400);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+impl Session {
+    /// Its line in the session log: when it started and ended in UTC, and its phase, separated
+    /// by tabs.
+    pub fn log_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\n",
+            utc(self.start),
+            utc(self.end),
+            self.phase.name()
+        )
+    }
+
+    /// Appends its line to the log at `path`, creating the file and its directory if needed.
+    pub fn append_to_log(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(self.log_line().as_bytes())
+    }
+}
+
+/// Goes through work and break phases on a [`WallClock`], each a countdown that starts the next
+/// one when it is over.
+pub struct Pomodoro<C: WallClock + Clone = SystemClock> {
+    clock: C,
+    settings: Settings,
+    phase: Phase,
+    timer: Timer<C>,
+    /* when the phase was started, for the log */
+    started: i64,
+    /* work sessions done */
+    completed: u32,
+}
+
+impl<C: WallClock + Clone> Pomodoro<C> {
+    /// Starts working.
+    pub fn new(clock: C, settings: Settings) -> Self {
+        let timer = Timer::new(clock.clone(), Mode::Countdown(settings.work));
+        let mut pomodoro = Pomodoro {
+            clock,
+            settings,
+            phase: Phase::Work,
+            timer,
+            started: 0,
+            completed: 0,
+        };
+        pomodoro.begin(Phase::Work);
+        pomodoro
+    }
+
+    fn begin(&mut self, phase: Phase) {
+        self.phase = phase;
+        self.timer = Timer::new(
+            self.clock.clone(),
+            Mode::Countdown(self.settings.duration(phase)),
+        );
+        self.timer.start();
+        self.started = self.clock.now();
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Number of work sessions done.
+    pub fn completed(&self) -> u32 {
+        self.completed
+    }
+
+    /// The countdown of the phase.
+    pub fn timer(&self) -> &Timer<C> {
+        &self.timer
+    }
+
+    /// How much of the phase is done, from `0.0` to `1.0`.
+    pub fn progress(&self) -> f64 {
+        self.timer.elapsed().as_secs_f64() / self.settings.duration(self.phase).as_secs_f64()
+    }
+
+    /// Pauses the phase, or carries on with it.
+    pub fn toggle(&mut self) {
+        self.timer.toggle();
+    }
+
+    /// Starts the phase again from the beginning.
+    pub fn restart(&mut self) {
+        self.begin(self.phase);
+    }
+
+    /// Starts the next phase if this one is over, and returns the one that ended.
+    pub fn update(&mut self) -> Option<Session> {
+        if !self.timer.is_finished() {
+            return None;
+        }
+        let session = Session {
+            phase: self.phase,
+            start: self.started,
+            end: self.clock.now(),
+        };
+        let next = match self.phase {
+            Phase::Work => {
+                self.completed += 1;
+                if self.completed.is_multiple_of(self.settings.sessions) {
+                    Phase::LongBreak
+                } else {
+                    Phase::ShortBreak
+                }
+            }
+            Phase::ShortBreak | Phase::LongBreak => Phase::Work,
+        };
+        self.begin(next);
+        Some(session)
+    }
+}
+
+#[test]
+fn test_pomodoro() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[derive(Clone, Default)]
+    struct FakeClock(Rc<Cell<i64>>);
+    impl WallClock for FakeClock {
+        fn now(&self) -> i64 {
+            self.0.get()
+        }
+    }
+
+    assert_eq!(Settings::parse("25m,5m,15m,4"), Some(Settings::default()));
+    let settings = Settings::parse("50m, 10m").unwrap();
+    assert_eq!(
+        (settings.work, settings.short_break, settings.sessions),
+        (Duration::from_secs(3000), Duration::from_secs(600), 4)
+    );
+    for bad in ["", "25m,0m", "25m,5m,15m,0", "25m,5m,15m,4,1", "25"] {
+        assert_eq!(Settings::parse(bad), None, "{}", bad);
+    }
+
+    let clock = FakeClock::default();
+    clock.0.set(1637831210);
+    let settings = Settings::parse("25m,5m,15m,2").unwrap();
+    let mut pomodoro = Pomodoro::new(clock.clone(), settings);
+    assert_eq!(pomodoro.phase(), Phase::Work);
+    clock.0.set(1637831210 + 5 * 60);
+    assert_eq!(pomodoro.progress(), 0.2);
+    assert_eq!(pomodoro.update(), None);
+
+    /* paused, it does not end */
+    pomodoro.toggle();
+    clock.0.set(1637831210 + 60 * 60);
+    assert_eq!(pomodoro.update(), None);
+    pomodoro.toggle();
+    clock.0.set(1637831210 + 80 * 60);
+    let session = pomodoro.update().unwrap();
+    assert_eq!(
+        session.log_line(),
+        "2021-11-25T09:06:50Z\t2021-11-25T10:26:50Z\twork\n"
+    );
+
+    let mut phases = vec![pomodoro.phase()];
+    for _ in 0..4 {
+        clock.0.set(clock.0.get() + 25 * 60);
+        assert!(pomodoro.update().is_some());
+        phases.push(pomodoro.phase());
+    }
+    assert_eq!(
+        phases,
+        [
+            Phase::ShortBreak,
+            Phase::Work,
+            Phase::LongBreak,
+            Phase::Work,
+            Phase::ShortBreak
+        ]
+    );
+    assert_eq!(pomodoro.completed(), 3);
+}
@@ -0,0 +1,976 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::*;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TailKind {
+    /// The default drop shaped tail.
+    #[default]
+    Drop,
+    /// A hooked tail.
+    Hook,
+}
+
+/// What to draw, the equivalent of the command line flags.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Config {
+    pub tail: TailKind,
+    /// Show an italian greyhound named Gaius Octavius Maximus instead of a cat.
+    pub dog: bool,
+    /// Show month date.
+    pub date: bool,
+    /// Show sun or moon phase depending on the hour.
+    pub sunmoon: bool,
+    /// Show only moon phase.
+    pub moon: bool,
+    /// Write the lit percentage of the moon under it.
+    pub moon_percent: bool,
+    /// Integer zoom factor of the framebuffer.
+    pub scale: usize,
+    /// Draw the hands and tail with smooth edges.
+    pub antialias: bool,
+    /// Sweep the second and minute hands round with the fractions of seconds, instead of
+    /// ticking once a second.
+    pub sweep: bool,
+    /// Colours of every part of the clock.
+    pub theme: Theme,
+    /// Where the clock is, to tell night from day by the sun instead of the hour.
+    pub location: Option<solar::Location>,
+    /// Which way up to draw the moon, or `None` for the hemisphere of `location` and northern
+    /// without one.
+    pub hemisphere: Option<moonphase::Hemisphere>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            tail: TailKind::default(),
+            dog: false,
+            date: false,
+            sunmoon: false,
+            moon: false,
+            moon_percent: false,
+            scale: 1,
+            antialias: false,
+            sweep: false,
+            theme: Theme::default(),
+            location: None,
+            hemisphere: None,
+        }
+    }
+}
+
+/// How the cat reacts to its clock.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Reaction {
+    #[default]
+    Calm,
+    /// An alarm rings: the cat stares with wide eyes and the hands flash.
+    Alarmed,
+    /// A countdown is over: the tail stops in the middle, the eyes dart around and the hands
+    /// flash.
+    TimeUp,
+    /// The clock strikes the hour: the cat blinks each time its tail reaches a side.
+    Chiming,
+}
+
+/// How far down the upper lids of the cat are.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Lids {
+    #[default]
+    Open,
+    /// Half closed: sleepy, or halfway through a blink.
+    Half,
+    /// Closed, in a blink.
+    Closed,
+}
+
+impl Lids {
+    /* How much of the eyes they cover; closed lids leave a sliver of white under them. */
+    fn closed(self) -> f64 {
+        match self {
+            Lids::Open => 0.0,
+            Lids::Half => 0.5,
+            Lids::Closed => 0.85,
+        }
+    }
+}
+
+/// Draws the clock into a `CAT_WIDTH x CAT_HEIGHT` framebuffer, or `scale` times that, without
+/// any windowing.
+///
+/// The tail and eye frames are precomputed on creation; `frame` arguments index into them and
+/// must be less than [`NUM_TAILS`].
+pub struct ClockRenderer {
+    config: Config,
+    body: Vec<u32>,
+    tails_frames: Vec<Image>,
+    eyes_frames: Vec<Image>,
+    sun: Image,
+    sun_bg: Image,
+    moon_corners: Image,
+    full_moon: Image,
+    wide_eyes: Image,
+    /* half closed and closed lids, to draw over the eyes */
+    lids_frames: [Image; 2],
+    lids: Lids,
+    reaction: Reaction,
+    /* what the hands show instead of the time */
+    duration: Option<Duration>,
+    /* seconds the hands are ahead of the time */
+    hands_offset: i64,
+    /* how much of a Pomodoro phase is done */
+    progress: Option<f64>,
+    /* where the tail, eyes and hands can be in any frame */
+    moving: [Rect; 3],
+}
+
+/* x, y, width and height of a part of the framebuffer */
+type Rect = (usize, usize, usize, usize);
+
+/* Where image is drawn at scale. */
+fn bounds(image: &Image, scale: usize) -> Rect {
+    (
+        image.x_offset * scale,
+        image.y_offset * scale,
+        image.width * scale,
+        image.height * scale,
+    )
+}
+
+fn union(a: Rect, b: Rect) -> Rect {
+    let (x, y) = (a.0.min(b.0), a.1.min(b.1));
+    (
+        x,
+        y,
+        (a.0 + a.2).max(b.0 + b.2) - x,
+        (a.1 + a.3).max(b.1 + b.3) - y,
+    )
+}
+
+impl ClockRenderer {
+    pub fn new(mut config: Config) -> Self {
+        config.scale = config.scale.max(1);
+        let scale = config.scale;
+        let tail_kind: fn(_, _, _) -> _ = match config.tail {
+            TailKind::Drop => create_tail_image,
+            TailKind::Hook => create_tail_image_hook,
+        };
+        let dog = config.dog;
+        let mut tails_frames: Vec<Image> = Vec::with_capacity(NUM_TAILS);
+        let mut eyes_frames: Vec<Image> = Vec::with_capacity(NUM_TAILS);
+
+        for i in 0..NUM_TAILS {
+            if dog {
+                tails_frames.push(dog_tail(i as f64 * PI / (NUM_TAILS as f64), scale));
+            } else {
+                tails_frames.push(tail_kind(
+                    i as f64 * PI / (NUM_TAILS as f64),
+                    scale,
+                    config.antialias,
+                ));
+            }
+            eyes_frames.push(create_eye_pixmap(
+                i as f64 * PI / (NUM_TAILS as f64),
+                dog,
+                scale,
+            ));
+        }
+
+        let body = draw_body(&config);
+        let wide_eyes = create_wide_eyes_pixmap(dog, scale);
+        let lids_frames =
+            [Lids::Half, Lids::Closed].map(|lids| create_lids_pixmap(lids.closed(), dog, scale));
+        let moving = [
+            tails_frames
+                .iter()
+                .map(|tail| bounds(tail, 1))
+                .reduce(union),
+            eyes_frames
+                .iter()
+                .chain([&wide_eyes])
+                .chain(&lids_frames)
+                .map(|eyes| bounds(eyes, 1))
+                .reduce(union),
+            Some(bounds(&hands::face(scale), 1)),
+        ]
+        .map(Option::unwrap);
+
+        ClockRenderer {
+            config,
+            body,
+            tails_frames,
+            eyes_frames,
+            sun: moonphase::sun(),
+            sun_bg: moonphase::sun_background(),
+            moon_corners: moonphase::corner_fill(),
+            full_moon: moonphase::MoonPosition::FullMoon.into(),
+            wide_eyes,
+            lids_frames,
+            lids: Lids::Open,
+            reaction: Reaction::Calm,
+            duration: None,
+            hands_offset: 0,
+            progress: None,
+            moving,
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Switches to `theme`, redrawing the static layers if it is a different one.
+    pub fn set_theme(&mut self, theme: Theme) {
+        if theme != self.config.theme {
+            self.config.theme = theme;
+            self.body = draw_body(&self.config);
+        }
+    }
+
+    pub fn set_reaction(&mut self, reaction: Reaction) {
+        self.reaction = reaction;
+    }
+
+    /// Lowers the lids over the eyes, e.g. to blink. An alarm still opens the eyes wide.
+    pub fn set_lids(&mut self, lids: Lids) {
+        self.lids = lids;
+    }
+
+    /// Makes the hands show `duration` as hours, minutes and seconds instead of the time, e.g.
+    /// the time left of a countdown, or the time again with `None`. The date, sun and moon keep
+    /// to the time.
+    pub fn show_duration(&mut self, duration: Option<Duration>) {
+        self.duration = duration;
+    }
+
+    /// Moves the hands `seconds` ahead of the time, e.g. to spin them round with `--crazy`. The
+    /// date, sun and moon keep to the time.
+    pub fn set_hands_offset(&mut self, seconds: i64) {
+        self.hands_offset = seconds;
+    }
+
+    /// Draws a ring around the face in the tie colour, `progress` (`0.0` to `1.0`) of the way
+    /// round, e.g. how much of a Pomodoro phase is done, or no ring with `None`.
+    pub fn set_progress(&mut self, progress: Option<f64>) {
+        self.progress = progress;
+    }
+
+    /* The time of the hands at time. */
+    fn hands_time(&self, time: &ClockTime) -> ClockTime {
+        let Some(duration) = self.duration else {
+            let seconds = (time.hour as i64 * 3600
+                + time.minutes as i64 * 60
+                + time.seconds as i64
+                + self.hands_offset)
+                .rem_euclid(86400);
+            return ClockTime {
+                hour: (seconds / 3600) as u8,
+                minutes: (seconds / 60 % 60) as u8,
+                seconds: (seconds % 60) as u64,
+                ..*time
+            };
+        };
+        let seconds = duration.as_secs();
+        ClockTime {
+            hour: (seconds / 3600 % 24) as u8,
+            minutes: (seconds / 60 % 60) as u8,
+            seconds: seconds % 60,
+            nanos: duration.subsec_nanos(),
+            ..*time
+        }
+    }
+
+    /* Whether to draw the hands at time, they flash every half second unless calm. */
+    fn hands_shown(&self, time: &ClockTime) -> bool {
+        self.reaction == Reaction::Calm || time.nanos < 500_000_000
+    }
+
+    /* The lids in tail/eyes frame, striking the hour the cat blinks with the tail at a side. */
+    fn lids(&self, frame: usize) -> Lids {
+        match self.reaction {
+            Reaction::Alarmed => Lids::Open,
+            Reaction::Chiming if frame == 0 || frame == NUM_TAILS - 1 => Lids::Closed,
+            _ => self.lids,
+        }
+    }
+
+    /* The tail frame to draw for tail/eyes frame. */
+    fn tail_frame(&self, frame: usize) -> usize {
+        if self.reaction == Reaction::TimeUp {
+            NUM_TAILS / 2
+        } else {
+            frame
+        }
+    }
+
+    /// Width of the framebuffer.
+    pub fn width(&self) -> usize {
+        CAT_WIDTH * self.config.scale
+    }
+
+    /// Height of the framebuffer.
+    pub fn height(&self) -> usize {
+        CAT_HEIGHT * self.config.scale
+    }
+
+    /// Renders tail/eyes frame `frame` at `time` into a new framebuffer.
+    pub fn render(&self, time: &ClockTime, frame: usize) -> Vec<u32> {
+        let mut buffer = vec![self.config.theme.background; self.width() * self.height()];
+        self.render_into(&mut buffer, time, frame);
+        buffer
+    }
+
+    /// Renders tail/eyes frame `frame` at `time` into `buffer`, overwriting it. `buffer` must be
+    /// `self.width() x self.height()` pixels.
+    pub fn render_into(&self, buffer: &mut [u32], time: &ClockTime, frame: usize) {
+        buffer.copy_from_slice(&self.body);
+        self.draw_moving_parts(buffer, time, frame);
+    }
+
+    /// Like [`render_into`](Self::render_into), for a `buffer` that already has the clock at
+    /// `previous` rendered by this renderer with the same theme: only the parts that can have
+    /// changed since are redrawn.
+    pub fn redraw_into(
+        &self,
+        buffer: &mut [u32],
+        previous: &ClockTime,
+        time: &ClockTime,
+        frame: usize,
+    ) {
+        let (row_width, scale) = (self.width(), self.config.scale);
+        let mut dirty = self.moving.to_vec();
+        for time in [previous, time] {
+            self.overlays(time, &mut |image, _, _| dirty.push(bounds(image, scale)));
+        }
+        for (x, y, width, height) in dirty {
+            let width = width.min(row_width - x);
+            for row in y..(y + height).min(self.height()) {
+                let start = row * row_width + x;
+                buffer[start..start + width].copy_from_slice(&self.body[start..start + width]);
+            }
+        }
+        self.draw_moving_parts(buffer, time, frame);
+    }
+
+    /* Draws the tail, eyes, hands and overlays over the body. */
+    fn draw_moving_parts(&self, buffer: &mut [u32], time: &ClockTime, frame: usize) {
+        let (row_width, scale) = (self.width(), self.config.scale);
+        let theme = &self.config.theme;
+
+        /* The moving parts are already drawn at scale. */
+        self.tails_frames[self.tail_frame(frame)]
+            .draw_scaled(buffer, row_width, 1, theme.tail, None);
+        let eyes = if self.reaction == Reaction::Alarmed {
+            &self.wide_eyes
+        } else {
+            &self.eyes_frames[frame]
+        };
+        eyes.draw_scaled(buffer, row_width, 1, theme.eyes, None);
+        match self.lids(frame) {
+            Lids::Open => {}
+            Lids::Half => self.lids_frames[0].draw_scaled(buffer, row_width, 1, theme.eyes, None),
+            Lids::Closed => self.lids_frames[1].draw_scaled(buffer, row_width, 1, theme.eyes, None),
+        }
+
+        if self.hands_shown(time) {
+            self.draw_hands(buffer, time);
+        }
+        self.overlays(time, &mut |image, fg, bg| {
+            image.draw_scaled(buffer, row_width, scale, fg, bg)
+        });
+    }
+
+    fn draw_hands(&self, buffer: &mut [u32], time: &ClockTime) {
+        let (row_width, scale) = (self.width(), self.config.scale);
+        let theme = &self.config.theme;
+        let (second, minute, hour) = hand_fractions(&self.hands_time(time), self.config.sweep);
+        let s = scale as i64;
+        let mut second_hand = hands::face(scale);
+        hands::draw_second(
+            &mut second_hand,
+            hands::SECOND_HAND_WIDTH * s,
+            hands::SECOND_HAND_HEIGHT * s,
+            -5 * s,
+            second,
+            self.config.antialias,
+        );
+        second_hand.draw_scaled(buffer, row_width, 1, theme.hands, None);
+        let mut minute_hand = hands::face(scale);
+        hands::draw_hand(
+            &mut minute_hand,
+            hands::MINUTE_HAND_WIDTH * s,
+            hands::MINUTE_HAND_HEIGHT * s,
+            -5 * s,
+            minute,
+            self.config.antialias,
+        );
+        minute_hand.draw_scaled(buffer, row_width, 1, theme.hands, None);
+        let mut hour_hand = hands::face(scale);
+        hands::draw_hand(
+            &mut hour_hand,
+            hands::HOUR_HAND_WIDTH * s,
+            hands::HOUR_HAND_HEIGHT * s,
+            -5 * s,
+            hour,
+            self.config.antialias,
+        );
+        hour_hand.draw_scaled(buffer, row_width, 1, theme.hands, None);
+    }
+
+    /// Renders tail/eyes frame `frame` at `time` as an SVG document.
+    ///
+    /// Hands and tail are drawn from their exact geometry, everything else is traced from the
+    /// bitmaps.
+    pub fn render_svg(&self, time: &ClockTime, frame: usize) -> String {
+        let theme = &self.config.theme;
+        let mut svg = svg::Svg::new(CAT_WIDTH, CAT_HEIGHT, theme.background);
+        let scale = self.config.scale;
+        svg.bitmap(CAT_WIDTH, CAT_HEIGHT, (0, 0), |x, y| {
+            Some(self.body[(y * self.width() + x) * scale]).filter(|c| *c != theme.background)
+        });
+
+        let t = self.tail_frame(frame) as f64 * PI / (NUM_TAILS as f64);
+        if self.config.dog {
+            svg.image(&dog_tail(t, 1), theme.tail, None);
+        } else {
+            let tail = match self.config.tail {
+                TailKind::Drop => tail_shapes(t),
+                TailKind::Hook => tail_shapes_hook(t),
+            };
+            for shape in &tail {
+                svg.shape(shape, theme.tail);
+            }
+        }
+        let eyes = if self.reaction == Reaction::Alarmed {
+            create_wide_eyes_pixmap(self.config.dog, 1)
+        } else {
+            create_eye_pixmap(frame as f64 * PI / (NUM_TAILS as f64), self.config.dog, 1)
+        };
+        svg.image(&eyes, theme.eyes, None);
+        let lids = self.lids(frame);
+        if lids != Lids::Open {
+            svg.image(
+                &create_lids_pixmap(lids.closed(), self.config.dog, 1),
+                theme.eyes,
+                None,
+            );
+        }
+
+        if self.hands_shown(time) {
+            let (second, minute, hour) = hand_fractions(&self.hands_time(time), self.config.sweep);
+            /* center of the face pixel */
+            let center = (
+                (hands::FACE_OFFSET_X + hands::FACE_WIDTH / 2) as f64 + 0.5,
+                (hands::FACE_OFFSET_Y + hands::FACE_HEIGHT / 2) as f64 + 0.5,
+            );
+            let to_face = |points: &[(f64, f64)]| {
+                points
+                    .iter()
+                    .map(|(x, y)| (center.0 + x, center.1 + y))
+                    .collect::<Vec<(f64, f64)>>()
+            };
+            for points in [
+                to_face(&hands::second_points(
+                    hands::SECOND_HAND_WIDTH as f64,
+                    hands::SECOND_HAND_HEIGHT as f64,
+                    -5.0,
+                    second,
+                )),
+                to_face(&hands::hand_points(
+                    hands::MINUTE_HAND_WIDTH as f64,
+                    hands::MINUTE_HAND_HEIGHT as f64,
+                    minute,
+                )),
+                to_face(&hands::hand_points(
+                    hands::HOUR_HAND_WIDTH as f64,
+                    hands::HOUR_HAND_HEIGHT as f64,
+                    hour,
+                )),
+            ] {
+                svg.shape(
+                    &svg::Shape::Polygon {
+                        points,
+                        fill: false,
+                    },
+                    theme.hands,
+                );
+            }
+        }
+
+        self.overlays(time, &mut |image, fg, bg| svg.image(image, fg, bg));
+        svg.finish()
+    }
+
+    /// Passes the progress ring, date and sun/moon images to `draw`, with the colours to draw
+    /// them with.
+    fn overlays(&self, time: &ClockTime, draw: &mut dyn FnMut(&Image, u32, Option<u32>)) {
+        let theme = &self.config.theme;
+        if let Some(progress) = self.progress {
+            draw(&hands::progress_ring(progress), theme.tie, None);
+        }
+        if self.config.date {
+            draw(&date::make_date(time.mday as i64), theme.date, None);
+        }
+
+        let Config { moon, sunmoon, .. } = self.config;
+        if !(moon || sunmoon) {
+            return;
+        }
+        let location = self.config.location.filter(|_| sunmoon);
+        match location.map(|location| (location, solar::elevation(&location, time.timestamp))) {
+            /* the sun by its height in the sky, until the end of civil twilight */
+            Some((location, elevation)) if elevation > solar::CIVIL_TWILIGHT => {
+                let horizon = if solar::is_morning(&location, time.timestamp) {
+                    theme.dawn
+                } else {
+                    theme.dusk
+                };
+                let (fg, bg) = sun_colors(theme, horizon, elevation);
+                if elevation >= solar::SUNRISE {
+                    draw(&self.sun_bg, bg, None);
+                    draw(&self.sun, fg, None);
+                } else {
+                    let depth =
+                        (elevation - solar::SUNRISE) / (solar::CIVIL_TWILIGHT - solar::SUNRISE);
+                    let (sun, sun_bg) = moonphase::sinking_sun(depth);
+                    draw(&sun_bg, bg, None);
+                    draw(&sun, fg, None);
+                }
+            }
+            Some(_) => self.draw_moon(time, draw),
+            None if moon || is_night(time, None) => self.draw_moon(time, draw),
+            None => {
+                draw(&self.sun_bg, theme.sun_dark, None);
+                draw(&self.sun, theme.sun, None);
+            }
+        }
+    }
+
+    fn draw_moon(&self, time: &ClockTime, draw: &mut dyn FnMut(&Image, u32, Option<u32>)) {
+        let theme = &self.config.theme;
+        let pos = moonphase::position(Some(time.timestamp));
+        draw(&self.moon_corners, theme.body, Some(theme.belly));
+        draw(&self.full_moon, theme.moon_dark, None);
+        let hemisphere = self.config.hemisphere.unwrap_or_else(|| {
+            self.config
+                .location
+                .map(|location| moonphase::Hemisphere::from_latitude(location.latitude))
+                .unwrap_or_default()
+        });
+        draw(&moonphase::lit(pos, hemisphere), theme.moon, None);
+        if self.config.moon_percent {
+            draw(&moonphase::illumination_label(pos), theme.moon, None);
+        }
+    }
+}
+
+/*
+ * Colours of the sun's outline and disc at elevation degrees: the horizon colour when it is
+ * low, the theme's as it climbs to FULL_DAYLIGHT.
+ */
+fn sun_colors(theme: &Theme, horizon: u32, elevation: f64) -> (u32, u32) {
+    const FULL_DAYLIGHT: f64 = 20.0;
+    let t = (elevation / FULL_DAYLIGHT).clamp(0.0, 1.0);
+    (
+        blend(horizon, theme.sun, 0.5 + t / 2.0),
+        blend(horizon, theme.sun_dark, t),
+    )
+}
+
+/// Draws the layers that never move: belly, body, tie and eye sockets.
+fn draw_body(config: &Config) -> Vec<u32> {
+    let (theme, scale, dog) = (config.theme, config.scale, config.dog);
+    let mut body: Vec<u32> = vec![theme.background; CAT_WIDTH * CAT_HEIGHT * scale * scale];
+    let row_width = CAT_WIDTH * scale;
+    let catwhite = Image::from(CATWHITE);
+    catwhite.draw_scaled(
+        &mut body,
+        row_width,
+        scale,
+        theme.belly,
+        Some(theme.background),
+    );
+    let catback = if dog {
+        Image::from(OCTAVEBACK)
+    } else {
+        Image::from(CATBACK)
+    };
+    catback.draw_scaled(&mut body, row_width, scale, theme.body, None);
+    if !dog {
+        let cattie = Image::from(CATTIE);
+        cattie.draw_scaled(&mut body, row_width, scale, theme.tie, None);
+    }
+    let tail = if dog {
+        Image::from(OCTAVETAIL)
+    } else {
+        Image::from(TAIL)
+    };
+    tail.draw_scaled(&mut body, row_width, scale, theme.background, None);
+    let eyes = Image::from(EYES);
+    eyes.draw_scaled(&mut body, row_width, scale, theme.eyes, None);
+    body
+}
+
+/// Whether it is night at `time`, when `--auto-dark` switches to the night theme.
+///
+/// Night is between sunset and sunrise at `location`, or from 19:00 to 8:00 without one. The
+/// moon is shown instead of the sun at night, or only after civil twilight with a location.
+pub fn is_night(time: &ClockTime, location: Option<&solar::Location>) -> bool {
+    match location {
+        Some(location) => !solar::is_daytime(location, time.timestamp),
+        None => time.hour < 8 || time.hour > 18,
+    }
+}
+
+/// Fractions of a circle, clockwise from high noon, of the second, minute and hour hands.
+///
+/// The hands tick from second to second and minute to minute, or `sweep` round in between.
+fn hand_fractions(time: &ClockTime, sweep: bool) -> (f64, f64, f64) {
+    if sweep {
+        let seconds = time.seconds as f64 + time.nanos as f64 / 1e9;
+        let minutes = time.minutes as f64 + seconds / 60.0;
+        return (
+            seconds / 60.0,
+            minutes / 60.0,
+            ((time.hour % 12) as f64 + minutes / 60.0) / 12.0,
+        );
+    }
+    (
+        (time.seconds as f64) / 60.0,
+        (6. * (time.minutes as f64)) / 360.,
+        0.5 * (((time.hour as usize % 12) * 60 + (time.minutes as usize)) as f64) / 360.,
+    )
+}
+
+/// Advances the tail/eyes frame index, swinging up to the last frame and back down to the first.
+///
+/// Returns the new index and direction.
+pub fn next_frame(mut frame: usize, mut up: bool) -> (usize, bool) {
+    if up {
+        if frame + 1 == NUM_TAILS {
+            up = false;
+        } else {
+            frame = (frame + 1).wrapping_rem(NUM_TAILS);
+        }
+    } else if frame == 0 {
+        up = true;
+    } else {
+        frame -= 1;
+    }
+    (frame, up)
+}
+
+/// Frame indices of one full swing of the tail, as shown by the main loop.
+pub fn swing_cycle() -> Vec<usize> {
+    let mut ret = Vec::with_capacity(2 * NUM_TAILS);
+    let (mut frame, mut up) = (0, true);
+    for _ in 0..(2 * NUM_TAILS) {
+        ret.push(frame);
+        (frame, up) = next_frame(frame, up);
+    }
+    debug_assert_eq!((frame, up), (0, true));
+    ret
+}
+
+#[cfg(test)]
+const MORNING: ClockTime = ClockTime {
+    hour: 10,
+    minutes: 8,
+    seconds: 42,
+    nanos: 0,
+    mday: 25,
+    wday: 4,
+    timestamp: 1637831210,
+};
+
+#[cfg(test)]
+const NIGHT: ClockTime = ClockTime {
+    hour: 22,
+    minutes: 47,
+    seconds: 3,
+    mday: 26,
+    ..MORNING
+};
+
+/* A renderer with the dark theme, the date, and the sun or moon. */
+#[cfg(test)]
+fn night_renderer() -> ClockRenderer {
+    ClockRenderer::new(Config {
+        date: true,
+        sunmoon: true,
+        theme: Theme::DARK,
+        ..Config::default()
+    })
+}
+
+#[test]
+fn test_render() {
+    let renderer = ClockRenderer::new(Config {
+        date: true,
+        sunmoon: true,
+        ..Config::default()
+    });
+    let time = MORNING;
+    let buffer = renderer.render(&time, 0);
+    assert_eq!(buffer.len(), CAT_WIDTH * CAT_HEIGHT);
+    assert_eq!(buffer, renderer.render(&time, 0));
+    assert_ne!(buffer, renderer.render(&time, NUM_TAILS - 1));
+    assert!(buffer.contains(&AZURE_BLUE));
+    assert!(buffer.contains(&SUN));
+
+    let scaled = ClockRenderer::new(Config {
+        scale: 2,
+        ..*renderer.config()
+    });
+    let scaled_buffer = scaled.render(&time, 0);
+    assert_eq!(scaled_buffer.len(), 4 * buffer.len());
+    /* static layers are blown up, the moving parts are drawn at scale */
+    for color in [AZURE_BLUE, SUN, SUNDARK] {
+        assert_eq!(
+            scaled_buffer.iter().filter(|c| **c == color).count(),
+            4 * buffer.iter().filter(|c| **c == color).count()
+        );
+    }
+
+    let cycle = swing_cycle();
+    assert_eq!(cycle.len(), 2 * NUM_TAILS);
+    assert_eq!(&cycle[NUM_TAILS - 1..NUM_TAILS + 1], &[NUM_TAILS - 1; 2]);
+}
+
+#[test]
+fn test_theme() {
+    let mut renderer = ClockRenderer::new(Config {
+        date: true,
+        sunmoon: true,
+        ..Config::default()
+    });
+    renderer.set_theme(Theme::DARK);
+    let themed = renderer.render(&MORNING, 0);
+    assert_eq!(themed, night_renderer().render(&MORNING, 0));
+    assert!(!themed.contains(&AZURE_BLUE));
+    assert!(themed.contains(&Theme::DARK.tie));
+    assert_eq!(themed[0], Theme::DARK.background);
+}
+
+#[test]
+fn test_is_night() {
+    let time = MORNING;
+    assert!(!is_night(&time, None));
+    assert!(is_night(&ClockTime { hour: 22, ..time }, None));
+    /* 2021-11-25 09:06 UTC is morning in Helsinki, four hours later it is night in Singapore */
+    let singapore = solar::Location::parse("1.29,103.85");
+    let helsinki = solar::Location::parse("60.17,24.94");
+    assert!(!is_night(&time, helsinki.as_ref()));
+    assert!(is_night(
+        &ClockTime {
+            timestamp: time.timestamp + 4 * 3600,
+            ..time
+        },
+        singapore.as_ref()
+    ));
+}
+
+#[test]
+fn test_moon() {
+    /* Sydney sees the moon mirrored */
+    let moon = |location, hemisphere| {
+        ClockRenderer::new(Config {
+            moon: true,
+            location,
+            hemisphere,
+            ..Config::default()
+        })
+        .render(&MORNING, 0)
+    };
+    let sydney = solar::Location::parse("-33.87,151.21");
+    let helsinki = solar::Location::parse("60.17,24.94");
+    let southern = moon(None, Some(moonphase::Hemisphere::Southern));
+    assert_eq!(moon(sydney, None), southern);
+    assert_ne!(moon(helsinki, None), southern);
+    assert_eq!(moon(None, None), moon(helsinki, None));
+}
+
+#[test]
+fn test_sun_colours() {
+    /* the sun is coloured by its height, and sets through twilight before the moon shows */
+    let sky = |timestamp| {
+        ClockRenderer::new(Config {
+            sunmoon: true,
+            location: solar::Location::parse("60.17,24.94"),
+            ..Config::default()
+        })
+        .render(
+            &ClockTime {
+                timestamp,
+                ..MORNING
+            },
+            0,
+        )
+    };
+    /* 2021-06-21 solar noon, 2021-12-21 20 minutes after sunset, and 20:00 */
+    assert!(sky(1624270920).contains(&SUN));
+    let dusk = sky(1640093580);
+    assert!(dusk.contains(&Theme::CLASSIC.dusk));
+    assert!(!dusk.contains(&SUN) && !dusk.contains(&MOON));
+    assert!(sky(1640109600).contains(&MOON));
+}
+
+#[test]
+fn test_redraw() {
+    /* redrawing what changed since the last frame is the same as drawing it afresh */
+    let renderer = night_renderer();
+    let mut buffer = renderer.render(&MORNING, 0);
+    renderer.redraw_into(&mut buffer, &MORNING, &NIGHT, NUM_TAILS - 1);
+    assert_eq!(buffer, renderer.render(&NIGHT, NUM_TAILS - 1));
+}
+
+#[test]
+fn test_sweep() {
+    /* sweeping hands move on between the seconds */
+    let renderer = night_renderer();
+    let half = ClockTime {
+        nanos: 500_000_000,
+        ..NIGHT
+    };
+    assert_eq!(renderer.render(&half, 0), renderer.render(&NIGHT, 0));
+    let sweep = ClockRenderer::new(Config {
+        sweep: true,
+        ..*renderer.config()
+    });
+    assert_ne!(sweep.render(&half, 0), sweep.render(&NIGHT, 0));
+    assert_eq!(
+        hand_fractions(&ClockTime { seconds: 0, ..half }, true),
+        (
+            0.5 / 60.0,
+            (47.0 + 0.5 / 60.0) / 60.0,
+            (10.0 + (47.0 + 0.5 / 60.0) / 60.0) / 12.0
+        )
+    );
+}
+
+#[test]
+fn test_alarm_reaction() {
+    /* a ringing alarm opens the eyes wide and flashes the hands */
+    let mut renderer = night_renderer();
+    let half = ClockTime {
+        nanos: 500_000_000,
+        ..NIGHT
+    };
+    let calm = renderer.render(&NIGHT, 0);
+    let mut buffer = calm.clone();
+    renderer.set_reaction(Reaction::Alarmed);
+    let ringing = renderer.render(&NIGHT, 0);
+    assert_ne!(ringing, calm);
+    assert_ne!(renderer.render(&half, 0), ringing);
+    renderer.redraw_into(&mut buffer, &NIGHT, &half, 0);
+    assert_eq!(buffer, renderer.render(&half, 0));
+    /* at the end of a countdown the tail stops in the middle */
+    renderer.set_reaction(Reaction::TimeUp);
+    assert_ne!(renderer.render(&NIGHT, 0), calm);
+}
+
+#[test]
+fn test_timer_hands() {
+    /* the hands show a duration, while the sun and moon keep to the time */
+    let mut renderer = night_renderer();
+    let calm = renderer.render(&NIGHT, 0);
+    renderer.show_duration(Some(Duration::from_secs(10 * 3600 + 8 * 60 + 42)));
+    let timer = renderer.render(&NIGHT, 0);
+    assert_ne!(timer, calm);
+    assert!(timer.contains(&Theme::DARK.moon));
+    renderer.show_duration(None);
+    assert_eq!(renderer.render(&NIGHT, 0), calm);
+}
+
+#[test]
+fn test_progress_ring() {
+    /* progress goes round the face in the tie colour */
+    let mut renderer = night_renderer();
+    let calm = renderer.render(&NIGHT, 0);
+    let tie = |buffer: &[u32]| buffer.iter().filter(|c| **c == Theme::DARK.tie).count();
+    let mut buffer = calm.clone();
+    renderer.set_progress(Some(0.25));
+    let quarter = renderer.render(&NIGHT, 0);
+    renderer.redraw_into(&mut buffer, &NIGHT, &NIGHT, 0);
+    assert_eq!(buffer, quarter);
+    renderer.set_progress(Some(0.5));
+    assert!(tie(&renderer.render(&NIGHT, 0)) > tie(&quarter));
+    assert!(tie(&quarter) > tie(&calm));
+}
+
+#[test]
+fn test_lids() {
+    /* blinks and sleepy lids are drawn over the eyes, and redrawn */
+    let mut renderer = night_renderer();
+    let calm = renderer.render(&NIGHT, 0);
+    let mut buffer = calm.clone();
+    renderer.set_lids(Lids::Half);
+    let sleepy = renderer.render(&NIGHT, 0);
+    assert_ne!(sleepy, calm);
+    renderer.redraw_into(&mut buffer, &NIGHT, &NIGHT, 0);
+    assert_eq!(buffer, sleepy);
+    renderer.set_lids(Lids::Closed);
+    assert_ne!(renderer.render(&NIGHT, 0), sleepy);
+    /* an alarm opens them wide */
+    renderer.set_reaction(Reaction::Alarmed);
+    let ringing = renderer.render(&NIGHT, 0);
+    renderer.set_lids(Lids::Open);
+    assert_eq!(renderer.render(&NIGHT, 0), ringing);
+
+    /* striking the hour, the cat blinks with the tail at a side */
+    renderer.set_reaction(Reaction::Chiming);
+    assert_ne!(renderer.render(&NIGHT, 0), calm);
+    assert_eq!(
+        renderer.render(&NIGHT, NUM_TAILS / 2),
+        night_renderer().render(&NIGHT, NUM_TAILS / 2)
+    );
+}
+
+#[test]
+fn test_hands_offset() {
+    let mut renderer = ClockRenderer::new(Config {
+        date: true,
+        ..Config::default()
+    });
+    let time = ClockTime {
+        hour: 23,
+        minutes: 8,
+        seconds: 42,
+        nanos: 0,
+        mday: 25,
+        wday: 4,
+        timestamp: 1637881722,
+    };
+    let later = ClockTime {
+        hour: 1,
+        minutes: 10,
+        seconds: 0,
+        ..time
+    };
+    let shown = renderer.render(&later, 0);
+    renderer.set_hands_offset(2 * 3600 + 78);
+    /* the hands go round past midnight, the date stays */
+    assert_eq!(renderer.render(&time, 0), shown);
+    renderer.set_hands_offset(0);
+    assert_ne!(renderer.render(&time, 0), shown);
+}
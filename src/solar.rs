@@ -0,0 +1,201 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Sunrise and sunset times, with the sunrise equation:
+//! <https://en.wikipedia.org/wiki/Sunrise_equation>
+
+/// A place on Earth in decimal degrees, north and east are positive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Location {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Location {
+    /// Parses `LAT,LON`, e.g. `60.17,24.94` for Helsinki or `1.29,103.85` for Singapore.
+    pub fn parse(s: &str) -> Option<Location> {
+        let (latitude, longitude) = s.split_once(',')?;
+        let latitude = latitude.trim().parse::<f64>().ok()?;
+        let longitude = longitude.trim().parse::<f64>().ok()?;
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return None;
+        }
+        Some(Location {
+            latitude,
+            longitude,
+        })
+    }
+}
+
+/// When the sun is up during a day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Daylight {
+    /// The sun rises and sets at these UNIX timestamps.
+    RisesAndSets { sunrise: i64, sunset: i64 },
+    /// The sun does not set (midnight sun).
+    PolarDay,
+    /// The sun does not rise.
+    PolarNight,
+}
+
+/* Julian date of the UNIX epoch */
+const UNIX_EPOCH_JD: f64 = 2440587.5;
+/* Julian date of 2000-01-01 12:00 UTC */
+const J2000: f64 = 2451545.0;
+/* Obliquity of the ecliptic */
+const EARTH_TILT: f64 = 23.4397;
+
+/// Elevation of the sun at sunrise and sunset, in degrees: refraction lifts the top of its
+/// disc over the horizon while its center is still below.
+pub const SUNRISE: f64 = -0.833;
+/// Elevation of the sun at the end of civil twilight, in degrees.
+pub const CIVIL_TWILIGHT: f64 = -6.0;
+
+pub(crate) fn julian_date(timestamp: i64) -> f64 {
+    timestamp as f64 / 86400.0 + UNIX_EPOCH_JD
+}
+
+pub(crate) fn timestamp(julian_date: f64) -> i64 {
+    ((julian_date - UNIX_EPOCH_JD) * 86400.0).round() as i64
+}
+
+/*
+ * Solar transit closest to timestamp as a Julian date, with the sine and cosine of the
+ * declination of the sun.
+ */
+fn transit(location: &Location, timestamp: i64) -> (f64, f64, f64) {
+    let sin = |deg: f64| deg.to_radians().sin();
+
+    /* Mean solar noon closest to timestamp, in days since J2000 */
+    let n = (julian_date(timestamp) - J2000 + location.longitude / 360.0).round();
+    let j_star = n - location.longitude / 360.0;
+    /* Solar mean anomaly */
+    let m = (357.5291 + 0.98560028 * j_star).rem_euclid(360.0);
+    /* Equation of the center */
+    let c = 1.9148 * sin(m) + 0.0200 * sin(2.0 * m) + 0.0003 * sin(3.0 * m);
+    /* Ecliptic longitude */
+    let lambda = (m + c + 180.0 + 102.9372).rem_euclid(360.0);
+    /* Solar transit */
+    let j_transit = J2000 + j_star + 0.0053 * sin(m) - 0.0069 * sin(2.0 * lambda);
+    /* Declination of the sun */
+    let sin_delta = sin(lambda) * sin(EARTH_TILT);
+    (j_transit, sin_delta, sin_delta.asin().cos())
+}
+
+/// Sunrise and sunset of the solar day, from midnight to midnight, that `timestamp` falls in.
+pub fn daylight(location: &Location, timestamp: i64) -> Daylight {
+    let sin = |deg: f64| deg.to_radians().sin();
+    let cos = |deg: f64| deg.to_radians().cos();
+
+    let (j_transit, sin_delta, cos_delta) = transit(location, timestamp);
+    /* Hour angle, the sun's disc touches the horizon at SUNRISE */
+    let cos_omega =
+        (sin(SUNRISE) - sin(location.latitude) * sin_delta) / (cos(location.latitude) * cos_delta);
+    if cos_omega < -1.0 {
+        return Daylight::PolarDay;
+    } else if cos_omega > 1.0 {
+        return Daylight::PolarNight;
+    }
+    let omega = cos_omega.acos().to_degrees();
+    Daylight::RisesAndSets {
+        sunrise: self::timestamp(j_transit - omega / 360.0),
+        sunset: self::timestamp(j_transit + omega / 360.0),
+    }
+}
+
+/// Height of the center of the sun above the horizon at `timestamp`, in degrees: negative when
+/// it is down, down to `-90.0`.
+pub fn elevation(location: &Location, timestamp: i64) -> f64 {
+    let sin = |deg: f64| deg.to_radians().sin();
+    let cos = |deg: f64| deg.to_radians().cos();
+
+    let (j_transit, sin_delta, cos_delta) = transit(location, timestamp);
+    let hour_angle = (julian_date(timestamp) - j_transit) * 360.0;
+    let sin_elevation =
+        sin(location.latitude) * sin_delta + cos(location.latitude) * cos_delta * cos(hour_angle);
+    sin_elevation.asin().to_degrees()
+}
+
+/// Whether `timestamp` is before solar noon, when the sun is rising.
+pub fn is_morning(location: &Location, timestamp: i64) -> bool {
+    julian_date(timestamp) < transit(location, timestamp).0
+}
+
+/// Whether the sun is up at `timestamp`.
+pub fn is_daytime(location: &Location, timestamp: i64) -> bool {
+    match daylight(location, timestamp) {
+        Daylight::RisesAndSets { sunrise, sunset } => (sunrise..sunset).contains(&timestamp),
+        Daylight::PolarDay => true,
+        Daylight::PolarNight => false,
+    }
+}
+
+#[test]
+fn test_daylight() {
+    const HELSINKI: Location = Location {
+        latitude: 60.17,
+        longitude: 24.94,
+    };
+    const SINGAPORE: Location = Location {
+        latitude: 1.29,
+        longitude: 103.85,
+    };
+    const LONGYEARBYEN: Location = Location {
+        latitude: 78.22,
+        longitude: 15.65,
+    };
+    let assert_close = |daylight: Daylight, sunrise: i64, sunset: i64| match daylight {
+        Daylight::RisesAndSets {
+            sunrise: rise,
+            sunset: set,
+        } => {
+            /* the equation is good to a couple of minutes */
+            assert!((rise - sunrise).abs() < 180, "{} != {}", rise, sunrise);
+            assert!((set - sunset).abs() < 180, "{} != {}", set, sunset);
+        }
+        other => panic!("{:?}", other),
+    };
+
+    /* 2021-06-21 12:00 UTC: Helsinki 03:54 to 22:50 EEST */
+    assert_close(daylight(&HELSINKI, 1624276800), 1624236840, 1624305000);
+    /* 2021-12-21 12:00 UTC: Helsinki 09:24 to 15:13 EET */
+    assert_close(daylight(&HELSINKI, 1640088000), 1640071440, 1640092380);
+    /* 2021-03-20 04:00 UTC: Singapore 07:11 to 19:17 SGT */
+    assert_close(daylight(&SINGAPORE, 1616212800), 1616195460, 1616239020);
+    assert_eq!(daylight(&LONGYEARBYEN, 1624276800), Daylight::PolarDay);
+    assert_eq!(daylight(&LONGYEARBYEN, 1640088000), Daylight::PolarNight);
+
+    /* 2021-12-21 at 12:00 and 18:00 local time */
+    assert!(is_daytime(&HELSINKI, 1640080800));
+    assert!(!is_daytime(&HELSINKI, 1640102400));
+    /* the sun is up as high as it gets at noon, and touches the horizon at sunrise */
+    /* 2021-06-21 10:22 UTC, solar noon in Helsinki at 90 - 60.17 + 23.44 degrees */
+    let noon = elevation(&HELSINKI, 1624270920);
+    assert!((noon - 53.27).abs() < 0.1, "{}", noon);
+    assert!((elevation(&HELSINKI, 1624236840) - SUNRISE).abs() < 0.2);
+    assert!(elevation(&HELSINKI, 1640102400) < CIVIL_TWILIGHT);
+    assert!(is_morning(&SINGAPORE, 1616212800));
+    assert!(!is_morning(&HELSINKI, 1640088000));
+
+    assert_eq!(Location::parse("60.17, 24.94"), Some(HELSINKI));
+    assert_eq!(Location::parse("91,0"), None);
+    assert_eq!(Location::parse("60.17"), None);
+}
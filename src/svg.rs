@@ -0,0 +1,207 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::*;
+use std::fmt::Write;
+
+/// A geometric primitive, in framebuffer coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    /// Closed polygon, either filled or only outlined with a one pixel wide stroke.
+    Polygon { points: Vec<(f64, f64)>, fill: bool },
+    /// Open path stroked with round caps and joins.
+    Polyline { points: Vec<(f64, f64)>, width: f64 },
+    /// Filled circle.
+    Circle { center: (f64, f64), radius: f64 },
+}
+
+fn color(c: u32) -> String {
+    format!("#{:06x}", c & 0xffffff)
+}
+
+fn points(points: &[(f64, f64)]) -> String {
+    points
+        .iter()
+        .map(|(x, y)| format!("{:.2},{:.2}", x, y))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// An SVG document of a `width x height` framebuffer, built up in drawing order.
+pub struct Svg {
+    width: usize,
+    height: usize,
+    content: String,
+}
+
+impl Svg {
+    /// Starts a document filled with `background`.
+    pub fn new(width: usize, height: usize, background: u32) -> Self {
+        let mut content = String::new();
+        let _ = writeln!(
+            content,
+            r#"<rect width="{}" height="{}" fill="{}"/>"#,
+            width,
+            height,
+            color(background)
+        );
+        Svg {
+            width,
+            height,
+            content,
+        }
+    }
+
+    /// Traces a `width` pixels wide bitmap placed at `(x_offset, y_offset)` into pixel exact
+    /// rectangles, one path per colour. Pixels for which `pixel` returns `None` are left out.
+    pub fn bitmap(
+        &mut self,
+        width: usize,
+        height: usize,
+        (x_offset, y_offset): (usize, usize),
+        pixel: impl Fn(usize, usize) -> Option<u32>,
+    ) {
+        let mut paths: Vec<(u32, String)> = vec![];
+        for y in 0..height {
+            let mut x = 0;
+            while x < width {
+                let Some(c) = pixel(x, y) else {
+                    x += 1;
+                    continue;
+                };
+                let start = x;
+                while x < width && pixel(x, y) == Some(c) {
+                    x += 1;
+                }
+                let idx = match paths.iter().position(|(pc, _)| *pc == c) {
+                    Some(idx) => idx,
+                    None => {
+                        paths.push((c, String::new()));
+                        paths.len() - 1
+                    }
+                };
+                let _ = write!(
+                    paths[idx].1,
+                    "M{} {}h{}v1h-{}z",
+                    x_offset + start,
+                    y_offset + y,
+                    x - start,
+                    x - start
+                );
+            }
+        }
+        for (c, d) in paths {
+            let _ = writeln!(
+                self.content,
+                r#"<path fill="{}" shape-rendering="crispEdges" d="{}"/>"#,
+                color(c),
+                d
+            );
+        }
+    }
+
+    /// Traces an `Image` the same way `Image::draw` paints it, except that pixels are either
+    /// painted or not: the ones at least half covered are.
+    pub fn image(&mut self, image: &Image, fg: u32, bg: Option<u32>) {
+        self.bitmap(
+            image.width,
+            image.height,
+            (image.x_offset, image.y_offset),
+            |x, y| {
+                if coverage(image.bytes[y * image.width + x]) >= 0.5 {
+                    Some(fg)
+                } else {
+                    bg
+                }
+            },
+        );
+    }
+
+    pub fn shape(&mut self, shape: &Shape, c: u32) {
+        let _ = match shape {
+            Shape::Polygon {
+                points: p,
+                fill: true,
+            } => writeln!(
+                self.content,
+                r#"<polygon points="{}" fill="{}"/>"#,
+                points(p),
+                color(c)
+            ),
+            Shape::Polygon {
+                points: p,
+                fill: false,
+            } => writeln!(
+                self.content,
+                r#"<polygon points="{}" fill="none" stroke="{}" stroke-width="1"/>"#,
+                points(p),
+                color(c)
+            ),
+            Shape::Polyline { points: p, width } => writeln!(
+                self.content,
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                points(p),
+                color(c),
+                width
+            ),
+            Shape::Circle { center, radius } => writeln!(
+                self.content,
+                r#"<circle cx="{:.2}" cy="{:.2}" r="{}" fill="{}"/>"#,
+                center.0,
+                center.1,
+                radius,
+                color(c)
+            ),
+        };
+    }
+
+    pub fn finish(self) -> String {
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n{}</svg>\n",
+            self.content,
+            w = self.width,
+            h = self.height,
+        )
+    }
+}
+
+#[test]
+fn test_svg() {
+    let mut image = Image::new(4, 2, 10, 20);
+    image.plot(1, 0);
+    image.plot(2, 0);
+    image.plot(3, 1);
+    let mut svg = Svg::new(CAT_WIDTH, CAT_HEIGHT, WHITE);
+    svg.image(&image, AZURE_BLUE, None);
+    svg.shape(
+        &Shape::Circle {
+            center: (1.0, 2.5),
+            radius: 3.0,
+        },
+        SUN,
+    );
+    let svg = svg.finish();
+    assert!(svg.contains(
+        r##"<path fill="#007fff" shape-rendering="crispEdges" d="M11 20h2v1h-2zM13 21h1v1h-1z"/>"##
+    ));
+    assert!(svg.contains(r##"<circle cx="1.00" cy="2.50" r="3" fill="#ffeb3b"/>"##));
+    assert!(svg.ends_with("</svg>\n"));
+}
@@ -0,0 +1,340 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::*;
+
+/// The colours of every part of the clock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    /// Around the cat.
+    pub background: u32,
+    /// The black body (`CATBACK`).
+    pub body: u32,
+    /// Belly, face and paws (`CATWHITE`).
+    pub belly: u32,
+    /// Bow tie (`CATTIE`).
+    pub tie: u32,
+    /// The tie while working in Pomodoro mode.
+    pub focus: u32,
+    /// The tie during Pomodoro breaks.
+    pub rest: u32,
+    pub eyes: u32,
+    pub hands: u32,
+    pub tail: u32,
+    /// Date box and digits.
+    pub date: u32,
+    /// The sun's outline and rays.
+    pub sun: u32,
+    /// The sun's disc.
+    pub sun_dark: u32,
+    /// The sun low in the sky in the morning, with a location.
+    pub dawn: u32,
+    /// The sun low in the sky in the evening, with a location.
+    pub dusk: u32,
+    /// The lit part of the moon.
+    pub moon: u32,
+    /// The dark part of the moon.
+    pub moon_dark: u32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::CLASSIC
+    }
+}
+
+impl Theme {
+    /// The original black and white cat with a blue tie.
+    pub const CLASSIC: Theme = Theme {
+        background: WHITE,
+        body: BLACK,
+        belly: WHITE,
+        tie: AZURE_BLUE,
+        focus: from_u8_rgb(0xe5, 0x39, 0x35),
+        rest: from_u8_rgb(0x43, 0xa0, 0x47),
+        eyes: BLACK,
+        hands: BLACK,
+        tail: BLACK,
+        date: BLACK,
+        sun: SUN,
+        sun_dark: SUNDARK,
+        dawn: from_u8_rgb(0xff, 0x8c, 0x1a),
+        dusk: from_u8_rgb(0xe0, 0x3c, 0x1e),
+        moon: MOON,
+        moon_dark: MOONDARK,
+    };
+
+    pub const DARK: Theme = Theme {
+        background: from_u8_rgb(0x2e, 0x34, 0x40),
+        body: from_u8_rgb(0x0f, 0x11, 0x15),
+        belly: from_u8_rgb(0xd8, 0xde, 0xe9),
+        tie: from_u8_rgb(0x5e, 0x81, 0xac),
+        focus: from_u8_rgb(0xbf, 0x61, 0x6a),
+        rest: from_u8_rgb(0xa3, 0xbe, 0x8c),
+        eyes: from_u8_rgb(0x0f, 0x11, 0x15),
+        hands: from_u8_rgb(0x0f, 0x11, 0x15),
+        tail: from_u8_rgb(0x0f, 0x11, 0x15),
+        date: from_u8_rgb(0x3b, 0x42, 0x52),
+        sun: from_u8_rgb(0xeb, 0xcb, 0x8b),
+        sun_dark: from_u8_rgb(0xd0, 0x87, 0x70),
+        dawn: from_u8_rgb(0xd0, 0x87, 0x70),
+        dusk: from_u8_rgb(0xbf, 0x61, 0x6a),
+        moon: MOON,
+        moon_dark: MOONDARK,
+    };
+
+    /// Ethan Schoonover's Solarized, light variant.
+    pub const SOLARIZED: Theme = Theme {
+        background: from_u8_rgb(0xfd, 0xf6, 0xe3),
+        body: from_u8_rgb(0x00, 0x2b, 0x36),
+        belly: from_u8_rgb(0xee, 0xe8, 0xd5),
+        tie: from_u8_rgb(0x26, 0x8b, 0xd2),
+        focus: from_u8_rgb(0xdc, 0x32, 0x2f),
+        rest: from_u8_rgb(0x85, 0x99, 0x00),
+        eyes: from_u8_rgb(0x00, 0x2b, 0x36),
+        hands: from_u8_rgb(0x07, 0x36, 0x42),
+        tail: from_u8_rgb(0x00, 0x2b, 0x36),
+        date: from_u8_rgb(0x58, 0x6e, 0x75),
+        sun: from_u8_rgb(0xb5, 0x89, 0x00),
+        sun_dark: from_u8_rgb(0xcb, 0x4b, 0x16),
+        dawn: from_u8_rgb(0xcb, 0x4b, 0x16),
+        dusk: from_u8_rgb(0xdc, 0x32, 0x2f),
+        moon: from_u8_rgb(0x93, 0xa1, 0xa1),
+        moon_dark: from_u8_rgb(0x58, 0x6e, 0x75),
+    };
+
+    pub const HIGH_CONTRAST: Theme = Theme {
+        background: WHITE,
+        body: BLACK,
+        belly: WHITE,
+        tie: from_u8_rgb(0xff, 0xff, 0x00),
+        focus: from_u8_rgb(0xff, 0x00, 0x00),
+        rest: from_u8_rgb(0x00, 0xc0, 0x00),
+        eyes: BLACK,
+        hands: BLACK,
+        tail: BLACK,
+        date: BLACK,
+        sun: from_u8_rgb(0xff, 0xff, 0x00),
+        sun_dark: from_u8_rgb(0xff, 0x80, 0x00),
+        dawn: from_u8_rgb(0xff, 0x80, 0x00),
+        dusk: from_u8_rgb(0xff, 0x00, 0x00),
+        moon: WHITE,
+        moon_dark: from_u8_rgb(0x80, 0x80, 0x80),
+    };
+
+    /// Names of the built-in themes, as accepted by [`Theme::by_name`].
+    pub const NAMES: [&'static str; 4] = ["classic", "dark", "solarized", "high-contrast"];
+
+    /// This theme with the tie coloured for Pomodoro `phase`.
+    pub fn with_phase(self, phase: pomodoro::Phase) -> Theme {
+        let tie = match phase {
+            pomodoro::Phase::Work => self.focus,
+            pomodoro::Phase::ShortBreak | pomodoro::Phase::LongBreak => self.rest,
+        };
+        Theme { tie, ..self }
+    }
+
+    /// Looks up a built-in theme.
+    pub fn by_name(name: &str) -> Option<Theme> {
+        match name {
+            "classic" => Some(Theme::CLASSIC),
+            "dark" => Some(Theme::DARK),
+            "solarized" => Some(Theme::SOLARIZED),
+            "high-contrast" => Some(Theme::HIGH_CONTRAST),
+            _ => None,
+        }
+    }
+
+    fn color_mut(&mut self, key: &str) -> Option<&mut u32> {
+        Some(match key {
+            "background" => &mut self.background,
+            "body" => &mut self.body,
+            "belly" => &mut self.belly,
+            "tie" => &mut self.tie,
+            "focus" => &mut self.focus,
+            "rest" => &mut self.rest,
+            "eyes" => &mut self.eyes,
+            "hands" => &mut self.hands,
+            "tail" => &mut self.tail,
+            "date" => &mut self.date,
+            "sun" => &mut self.sun,
+            "sun_dark" => &mut self.sun_dark,
+            "dawn" => &mut self.dawn,
+            "dusk" => &mut self.dusk,
+            "moon" => &mut self.moon,
+            "moon_dark" => &mut self.moon_dark,
+            _ => return None,
+        })
+    }
+
+    /// Parses a theme file.
+    ///
+    /// Theme files are a small subset of TOML: `key = "value"` pairs, one per line, with `#`
+    /// comments. Colours are written as `"#rrggbb"` or `"#rgb"` and the keys are the names of
+    /// the fields of [`Theme`]. An optional `base` key names a built-in theme to start from,
+    /// otherwise missing colours are taken from [`Theme::CLASSIC`]. A `[theme]` table header is
+    /// allowed, so that a theme can live in a larger configuration file.
+    pub fn parse(input: &str) -> Result<Theme, String> {
+        let mut pairs: Vec<(usize, &str, String)> = vec![];
+        for (i, line) in input.lines().enumerate() {
+            let line_no = i + 1;
+            let line = strip_comment(line).trim();
+            if line.is_empty() || line == "[theme]" {
+                continue;
+            }
+            let (key, value) = parse_pair(line_no, line)?;
+            pairs.push((line_no, key, value.to_string()));
+        }
+
+        let mut theme = Theme::CLASSIC;
+        if let Some((line_no, _, base)) = pairs.iter().find(|(_, key, _)| *key == "base") {
+            theme = Theme::by_name(base).ok_or_else(|| {
+                format!(
+                    "line {}: unknown base theme `{}`, expected one of {}",
+                    line_no,
+                    base,
+                    Theme::NAMES.join(", ")
+                )
+            })?;
+        }
+        for (line_no, key, value) in pairs {
+            if key == "base" {
+                continue;
+            }
+            let color = theme
+                .color_mut(key)
+                .ok_or_else(|| format!("line {}: unknown key `{}`", line_no, key))?;
+            *color = parse_color(&value).ok_or_else(|| {
+                format!(
+                    "line {}: `{}` is not a colour, expected \"#rrggbb\"",
+                    line_no, value
+                )
+            })?;
+        }
+        Ok(theme)
+    }
+
+    /// A built-in theme by name, or else the theme file at path `name_or_path`.
+    pub fn load(name_or_path: &str) -> Result<Theme, String> {
+        if let Some(theme) = Theme::by_name(name_or_path) {
+            return Ok(theme);
+        }
+        let input = std::fs::read_to_string(name_or_path).map_err(|err| {
+            format!(
+                "{} is neither a theme ({}) nor a readable file: {}",
+                name_or_path,
+                Theme::NAMES.join(", "),
+                err
+            )
+        })?;
+        Theme::parse(&input).map_err(|err| format!("{}: {}", name_or_path, err))
+    }
+}
+
+/* Splits a `key = "value"` line at line_no of a configuration file into key and value. */
+pub(crate) fn parse_pair(line_no: usize, line: &str) -> Result<(&str, &str), String> {
+    let (key, value) = line
+        .split_once('=')
+        .ok_or_else(|| format!("line {}: expected `key = \"value\"`", line_no))?;
+    let (key, value) = (key.trim(), value.trim());
+    let value = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .ok_or_else(|| format!("line {}: value of `{}` must be a string", line_no, key))?;
+    Ok((key, value))
+}
+
+/* Removes a `#` comment, but not a `#` inside a string. */
+pub(crate) fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('#', None) => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn parse_color(value: &str) -> Option<u32> {
+    let hex = value.strip_prefix('#')?;
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    match hex.len() {
+        6 => u32::from_str_radix(hex, 16).ok(),
+        3 => {
+            let c = u32::from_str_radix(hex, 16).ok()?;
+            let (r, g, b) = ((c >> 8) & 0xf, (c >> 4) & 0xf, c & 0xf);
+            Some(from_u8_rgb(
+                (r * 0x11) as u8,
+                (g * 0x11) as u8,
+                (b * 0x11) as u8,
+            ))
+        }
+        _ => None,
+    }
+}
+
+#[test]
+fn test_theme_parse() {
+    let theme = Theme::parse(
+        r##"
+# my cat
+[theme]
+base = "dark"
+tie = "#ff0000"   # red tie
+hands = '#0f0'
+"##,
+    )
+    .unwrap();
+    assert_eq!(
+        theme,
+        Theme {
+            tie: 0xff0000,
+            hands: 0x00ff00,
+            ..Theme::DARK
+        }
+    );
+    assert_eq!(Theme::parse("").unwrap(), Theme::CLASSIC);
+    for name in Theme::NAMES {
+        assert!(Theme::by_name(name).is_some());
+    }
+
+    assert_eq!(
+        Theme::parse("tie = \"#ff0000\"\nfur = \"#000000\""),
+        Err("line 2: unknown key `fur`".to_string())
+    );
+    assert!(Theme::parse("tie = #ff0000").is_err());
+    assert!(Theme::parse("tie = \"red\"").is_err());
+    assert!(Theme::parse("base = \"neon\"").is_err());
+    assert!(Theme::parse("tie").is_err());
+
+    let rest = Theme::CLASSIC.with_phase(pomodoro::Phase::LongBreak);
+    assert_eq!(rest.tie, Theme::CLASSIC.rest);
+    assert_eq!(
+        rest.with_phase(pomodoro::Phase::Work).tie,
+        Theme::CLASSIC.focus
+    );
+}
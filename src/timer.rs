@@ -0,0 +1,202 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Countdown timers and stopwatches, for the hands to show instead of the time of day.
+
+use crate::{SystemClock, WallClock};
+use std::time::Duration;
+
+/// Whether a [`Timer`] counts down or up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Counts down from a duration, and stops at zero.
+    Countdown(Duration),
+    /// Counts up from zero.
+    Stopwatch,
+}
+
+/// Parses a duration of hours, minutes and seconds, e.g. `25m`, `90s` or `1h30m`.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let mut seconds: u64 = 0;
+    let mut rest = s.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let n = rest[..digits].parse::<u64>().ok()?;
+        let unit = match rest[digits..].chars().next()? {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        seconds = seconds.checked_add(n.checked_mul(unit)?)?;
+        rest = &rest[digits + 1..];
+    }
+    Some(Duration::from_secs(seconds))
+}
+
+/// Counts time on a [`WallClock`], until it is paused.
+pub struct Timer<C: WallClock = SystemClock> {
+    clock: C,
+    mode: Mode,
+    /* time run before the last start */
+    run: Duration,
+    /* when it was last started, if it is running */
+    started: Option<(i64, u32)>,
+}
+
+impl<C: WallClock> Timer<C> {
+    /// A timer that has not started yet.
+    pub fn new(clock: C, mode: Mode) -> Self {
+        Timer {
+            clock,
+            mode,
+            run: Duration::ZERO,
+            started: None,
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started.is_some() && !self.is_finished()
+    }
+
+    pub fn start(&mut self) {
+        if self.started.is_none() {
+            self.started = Some(self.clock.now_precise());
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.run = self.elapsed();
+        self.started = None;
+    }
+
+    /// Starts a paused timer and pauses a running one. A countdown that is over starts again
+    /// from the beginning.
+    pub fn toggle(&mut self) {
+        if self.is_finished() {
+            self.reset();
+            self.start();
+        } else if self.started.is_some() {
+            self.pause();
+        } else {
+            self.start();
+        }
+    }
+
+    /// Goes back to the beginning, paused.
+    pub fn reset(&mut self) {
+        self.run = Duration::ZERO;
+        self.started = None;
+    }
+
+    /// Time run so far, up to the duration of a countdown.
+    pub fn elapsed(&self) -> Duration {
+        let mut elapsed = self.run;
+        if let Some((secs, nanos)) = self.started {
+            let (now_secs, now_nanos) = self.clock.now_precise();
+            let since =
+                (now_secs - secs) as i128 * 1_000_000_000 + now_nanos as i128 - nanos as i128;
+            /* the clock could have been set back */
+            elapsed += Duration::from_nanos(since.clamp(0, u64::MAX as i128) as u64);
+        }
+        match self.mode {
+            Mode::Countdown(duration) => elapsed.min(duration),
+            Mode::Stopwatch => elapsed,
+        }
+    }
+
+    /// Whether a countdown is over.
+    pub fn is_finished(&self) -> bool {
+        matches!(self.mode, Mode::Countdown(duration) if self.elapsed() >= duration)
+    }
+
+    /// What the hands show: the time left of a countdown, or the time run by a stopwatch.
+    pub fn shown(&self) -> Duration {
+        match self.mode {
+            Mode::Countdown(duration) => duration - self.elapsed(),
+            Mode::Stopwatch => self.elapsed(),
+        }
+    }
+}
+
+#[test]
+fn test_timer() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[derive(Clone, Default)]
+    struct FakeClock(Rc<Cell<(i64, u32)>>);
+    impl WallClock for FakeClock {
+        fn now(&self) -> i64 {
+            self.0.get().0
+        }
+
+        fn now_precise(&self) -> (i64, u32) {
+            self.0.get()
+        }
+    }
+
+    assert_eq!(parse_duration("25m"), Some(Duration::from_secs(25 * 60)));
+    assert_eq!(parse_duration("1h30m15s"), Some(Duration::from_secs(5415)));
+    assert_eq!(parse_duration("25"), None);
+    assert_eq!(parse_duration("25x"), None);
+    assert_eq!(parse_duration(""), None);
+
+    let clock = FakeClock::default();
+    clock.0.set((1637831210, 0));
+    let mut timer = Timer::new(clock.clone(), Mode::Countdown(Duration::from_secs(25 * 60)));
+    assert_eq!(timer.shown(), Duration::from_secs(25 * 60));
+    timer.start();
+    clock.0.set((1637831210 + 60, 500_000_000));
+    assert_eq!(timer.shown(), Duration::from_millis(24 * 60_000 - 500));
+
+    /* paused, it keeps its time */
+    timer.toggle();
+    assert!(!timer.is_running());
+    clock.0.set((1637831210 + 600, 0));
+    assert_eq!(timer.shown(), Duration::from_millis(24 * 60_000 - 500));
+    timer.toggle();
+    clock.0.set((1637831210 + 660, 0));
+    assert_eq!(timer.elapsed(), Duration::from_millis(120_500));
+
+    /* and stops at zero */
+    clock.0.set((1637831210 + 3600, 0));
+    assert!(timer.is_finished() && !timer.is_running());
+    assert_eq!(timer.shown(), Duration::ZERO);
+    timer.toggle();
+    assert!(timer.is_running());
+    assert_eq!(timer.shown(), Duration::from_secs(25 * 60));
+
+    let mut stopwatch = Timer::new(clock.clone(), Mode::Stopwatch);
+    stopwatch.start();
+    clock.0.set((1637831210 + 3600 + 4000, 0));
+    assert_eq!(stopwatch.shown(), Duration::from_secs(4000));
+    assert!(!stopwatch.is_finished());
+    stopwatch.reset();
+    assert_eq!(stopwatch.shown(), Duration::ZERO);
+}
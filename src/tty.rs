@@ -0,0 +1,228 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::*;
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/* Upper half block: the foreground colour paints the top pixel, the background the bottom one. */
+const UPPER_HALF_BLOCK: char = '\u{2580}';
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_interrupt(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Terminal size in columns and rows.
+pub fn terminal_size() -> Option<(usize, usize)> {
+    let mut ws = std::mem::MaybeUninit::<libc::winsize>::uninit();
+    let ret = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, ws.as_mut_ptr()) };
+    if ret != 0 {
+        return None;
+    }
+    let ws = unsafe { ws.assume_init() };
+    if ws.ws_col == 0 || ws.ws_row == 0 {
+        return None;
+    }
+    Some((ws.ws_col as usize, ws.ws_row as usize))
+}
+
+/// Puts the terminal in non-canonical mode on the alternate screen; restored on drop.
+pub struct Terminal {
+    termios: Option<libc::termios>,
+    size: Option<(usize, usize)>,
+    /* other keys than the ones to quit that were read */
+    pressed: Vec<u8>,
+}
+
+impl Terminal {
+    pub fn new() -> io::Result<Self> {
+        let mut termios = std::mem::MaybeUninit::<libc::termios>::uninit();
+        let termios = if unsafe { libc::tcgetattr(libc::STDIN_FILENO, termios.as_mut_ptr()) } == 0 {
+            let termios = unsafe { termios.assume_init() };
+            let mut raw = termios;
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+            raw.c_cc[libc::VMIN] = 0;
+            raw.c_cc[libc::VTIME] = 0;
+            unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) };
+            Some(termios)
+        } else {
+            None
+        };
+        unsafe {
+            libc::signal(
+                libc::SIGINT,
+                on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            );
+        }
+        /* alternate screen, hidden cursor */
+        let mut stdout = io::stdout();
+        stdout.write_all(b"\x1b[?1049h\x1b[?25l")?;
+        stdout.flush()?;
+        Ok(Terminal {
+            termios,
+            size: None,
+            pressed: vec![],
+        })
+    }
+
+    /// Returns `true` if the user asked to quit with `q`, `ESC` or `^C`.
+    pub fn should_quit(&mut self) -> bool {
+        let mut buf = [0; 16];
+        if self.termios.is_some() {
+            if let Ok(n) = io::stdin().read(&mut buf) {
+                if buf[..n].iter().any(|b| *b == b'q' || *b == 0x1b) {
+                    return true;
+                }
+                self.pressed.extend_from_slice(&buf[..n]);
+            }
+        }
+        INTERRUPTED.load(Ordering::SeqCst)
+    }
+
+    /// Returns the other keys pressed since the last call, as read by
+    /// [`should_quit`](Self::should_quit).
+    pub fn keys(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.pressed)
+    }
+
+    /// Draws a `width x height` framebuffer, fitted and centered in the terminal.
+    pub fn draw(&mut self, buffer: &[u32], width: usize, height: usize) -> io::Result<()> {
+        let size = terminal_size().unwrap_or((80, 24));
+        let mut stdout = io::stdout().lock();
+        if self.size != Some(size) {
+            self.size = Some(size);
+            stdout.write_all(b"\x1b[0m\x1b[2J")?;
+        }
+        let (cols, rows) = size;
+        stdout.write_all(b"\x1b[H")?;
+        stdout.write_all(half_blocks(buffer, width, height, cols, rows).as_bytes())?;
+        stdout.flush()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(b"\x1b[0m\x1b[?25h\x1b[?1049l");
+        let _ = stdout.flush();
+        if let Some(termios) = self.termios.take() {
+            unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) };
+        }
+    }
+}
+
+/// Averages the pixels of `buffer` in the rectangle `[x0, x1) x [y0, y1)`.
+fn average(
+    buffer: &[u32],
+    width: usize,
+    (x0, x1): (usize, usize),
+    (y0, y1): (usize, usize),
+) -> u32 {
+    let (mut r, mut g, mut b, mut n) = (0, 0, 0, 0);
+    for y in y0..y1 {
+        for pixel in &buffer[y * width + x0..y * width + x1] {
+            r += (pixel >> 16) & 0xff;
+            g += (pixel >> 8) & 0xff;
+            b += pixel & 0xff;
+            n += 1;
+        }
+    }
+    if n == 0 {
+        return WHITE;
+    }
+    from_u8_rgb((r / n) as u8, (g / n) as u8, (b / n) as u8)
+}
+
+/// Scales a `width x height` framebuffer to fit in `cols x rows` terminal cells and encodes it
+/// with half block characters and 24-bit colour escape sequences, two pixels per cell.
+pub fn half_blocks(
+    buffer: &[u32],
+    width: usize,
+    height: usize,
+    cols: usize,
+    rows: usize,
+) -> String {
+    let scale = f64::min(
+        cols as f64 / width as f64,
+        (2 * rows) as f64 / height as f64,
+    );
+    let out_width = ((width as f64 * scale) as usize).clamp(1, cols);
+    let out_height = ((height as f64 * scale) as usize / 2).clamp(1, rows) * 2;
+    let padding = (cols - out_width) / 2;
+    let src_x = |x: usize| x * width / out_width;
+    let src_y = |y: usize| y * height / out_height;
+    let pixel = |x: usize, y: usize| {
+        average(
+            buffer,
+            width,
+            (src_x(x), src_x(x + 1).max(src_x(x) + 1)),
+            (src_y(y), src_y(y + 1).max(src_y(y) + 1)),
+        )
+    };
+
+    let mut ret = String::with_capacity(out_width * out_height * 20);
+    for y in (0..out_height).step_by(2) {
+        ret.push_str("\x1b[0m\x1b[K");
+        ret.extend(std::iter::repeat_n(' ', padding));
+        let mut prev: Option<(u32, u32)> = None;
+        for x in 0..out_width {
+            let (fg, bg) = (pixel(x, y), pixel(x, y + 1));
+            if prev != Some((fg, bg)) {
+                ret.push_str(&format!(
+                    "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m",
+                    (fg >> 16) & 0xff,
+                    (fg >> 8) & 0xff,
+                    fg & 0xff,
+                    (bg >> 16) & 0xff,
+                    (bg >> 8) & 0xff,
+                    bg & 0xff
+                ));
+                prev = Some((fg, bg));
+            }
+            ret.push(UPPER_HALF_BLOCK);
+        }
+        ret.push_str("\x1b[0m");
+        if y + 2 < out_height {
+            ret.push_str("\r\n");
+        }
+    }
+    ret
+}
+
+#[test]
+fn test_half_blocks() {
+    let buffer = [WHITE, BLACK, AZURE_BLUE, WHITE];
+    assert_eq!(
+        half_blocks(&buffer, 2, 2, 4, 1),
+        "\x1b[0m\x1b[K \x1b[38;2;255;255;255m\x1b[48;2;0;127;255m\u{2580}\x1b[38;2;0;0;0m\x1b[48;2;255;255;255m\u{2580}\x1b[0m"
+    );
+    let ret = half_blocks(
+        &vec![WHITE; CAT_WIDTH * CAT_HEIGHT],
+        CAT_WIDTH,
+        CAT_HEIGHT,
+        80,
+        24,
+    );
+    assert_eq!(ret.matches('\u{2580}').count(), 24 * 24);
+    assert_eq!(ret.matches("\r\n").count(), 23);
+}
@@ -0,0 +1,478 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Time zones of the IANA time zone database, read from the TZif files of the system:
+//! <https://www.rfc-editor.org/rfc/rfc8536>
+
+/// Where the TZif files are, unless `TZDIR` is set.
+pub const ZONEINFO: &str = "/usr/share/zoneinfo";
+
+/// The UTC offsets of a place, past and future.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeZone {
+    /* transition times, ascending, with the index in offsets of the offset from then on */
+    transitions: Vec<(i64, usize)>,
+    /* UTC offsets in seconds, east of Greenwich is positive */
+    offsets: Vec<i64>,
+    /* for times after the last transition */
+    rule: Option<Rule>,
+}
+
+/* A date of a POSIX TZ string rule */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RuleDate {
+    /* Jn: day n of the year, 1 to 365, never counting February 29 */
+    Julian1(i64),
+    /* n: day n of the year, 0 to 365, counting February 29 */
+    Julian0(i64),
+    /* Mm.w.d: weekday d, 0 is Sunday, of week w of month m, week 5 is the last */
+    MonthWeekDay { month: i64, week: i64, weekday: i64 },
+}
+
+/*
+ * Daylight saving time of a POSIX TZ string: its offset, and when it starts and ends in
+ * seconds after local midnight of the rule's date.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Dst {
+    offset: i64,
+    start: (RuleDate, i64),
+    end: (RuleDate, i64),
+}
+
+/* The POSIX TZ string at the end of TZif files, like `EET-2EEST,M3.5.0/3,M10.5.0/4` */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Rule {
+    std_offset: i64,
+    dst: Option<Dst>,
+}
+
+impl TimeZone {
+    /// Parses a TZif file, of any version.
+    pub fn parse(data: &[u8]) -> Result<TimeZone, String> {
+        /* counts of the header: isutcnt, isstdcnt, leapcnt, timecnt, typecnt, charcnt */
+        fn header(data: &[u8]) -> Result<[usize; 6], String> {
+            if data.len() < 44 || &data[..4] != b"TZif" {
+                return Err("not a TZif file".to_string());
+            }
+            let mut counts = [0; 6];
+            for (i, count) in counts.iter_mut().enumerate() {
+                let bytes = &data[20 + 4 * i..24 + 4 * i];
+                *count = u32::from_be_bytes(bytes.try_into().unwrap()) as usize;
+            }
+            Ok(counts)
+        }
+        fn block_len(counts: [usize; 6], time_size: usize) -> usize {
+            let [isutcnt, isstdcnt, leapcnt, timecnt, typecnt, charcnt] = counts;
+            timecnt * (time_size + 1)
+                + typecnt * 6
+                + charcnt
+                + leapcnt * (time_size + 4)
+                + isstdcnt
+                + isutcnt
+        }
+
+        let counts = header(data)?;
+        let version = data[4];
+        /* version 2 and later repeat everything with 64-bit times after the version 1 data */
+        let (data, time_size) = if version >= b'2' {
+            let v2 = data
+                .get(44 + block_len(counts, 4)..)
+                .ok_or("truncated TZif file")?;
+            (v2, 8)
+        } else {
+            (data, 4)
+        };
+        let counts = header(data)?;
+        let [_, _, _, timecnt, typecnt, _] = counts;
+        let block = data
+            .get(44..44 + block_len(counts, time_size))
+            .ok_or("truncated TZif file")?;
+        if typecnt == 0 {
+            return Err("TZif file without local time types".to_string());
+        }
+
+        let times = &block[..timecnt * time_size];
+        let indices = &block[timecnt * time_size..timecnt * (time_size + 1)];
+        let types = &block[timecnt * (time_size + 1)..timecnt * (time_size + 1) + typecnt * 6];
+        let offsets = types
+            .chunks(6)
+            .map(|t| i32::from_be_bytes(t[..4].try_into().unwrap()) as i64)
+            .collect::<Vec<i64>>();
+        let mut transitions = Vec::with_capacity(timecnt);
+        for (time, &index) in times.chunks(time_size).zip(indices) {
+            let time = if time_size == 8 {
+                i64::from_be_bytes(time.try_into().unwrap())
+            } else {
+                i32::from_be_bytes(time.try_into().unwrap()) as i64
+            };
+            if index as usize >= typecnt {
+                return Err(format!(
+                    "transition to local time type {} of {}",
+                    index, typecnt
+                ));
+            }
+            transitions.push((time, index as usize));
+        }
+
+        let mut rule = None;
+        if version >= b'2' {
+            /* the footer, a POSIX TZ string between newlines */
+            let footer = &data[44 + block.len()..];
+            let footer = footer
+                .strip_prefix(b"\n")
+                .and_then(|f| f.split(|b| *b == b'\n').next())
+                .ok_or("TZif file without footer")?;
+            if !footer.is_empty() {
+                let footer = std::str::from_utf8(footer).map_err(|err| err.to_string())?;
+                rule = Some(
+                    Rule::parse(footer).ok_or_else(|| format!("invalid TZ string `{}`", footer))?,
+                );
+            }
+        }
+
+        Ok(TimeZone {
+            transitions,
+            offsets,
+            rule,
+        })
+    }
+
+    /// Loads the time zone `name`, e.g. `Europe/Athens`, from the directory in the `TZDIR`
+    /// environment variable or [`ZONEINFO`]. A `name` starting with `/` is the path of a TZif
+    /// file.
+    pub fn load(name: &str) -> Result<TimeZone, String> {
+        let path = if name.starts_with('/') {
+            std::path::PathBuf::from(name)
+        } else {
+            if name.split('/').any(|part| part == "..") {
+                return Err(format!("{} is not a time zone name", name));
+            }
+            std::env::var_os("TZDIR")
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(|| ZONEINFO.into())
+                .join(name)
+        };
+        let data = std::fs::read(&path).map_err(|err| {
+            format!(
+                "could not read time zone {} from {}: {}",
+                name,
+                path.display(),
+                err
+            )
+        })?;
+        TimeZone::parse(&data).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    /// A time zone of a POSIX `TZ` string, like `EET-2EEST,M3.5.0/3,M10.5.0/4`, that follows
+    /// the same rules every year.
+    pub fn from_posix(s: &str) -> Option<TimeZone> {
+        let rule = Rule::parse(s)?;
+        Some(TimeZone {
+            transitions: vec![],
+            offsets: vec![rule.std_offset],
+            rule: Some(rule),
+        })
+    }
+
+    /// Offset of local time from UTC at `timestamp`, in seconds east of Greenwich.
+    pub fn offset(&self, timestamp: i64) -> i64 {
+        let idx = self.transitions.partition_point(|(at, _)| *at <= timestamp);
+        match (idx, &self.rule) {
+            (idx, Some(rule)) if idx == self.transitions.len() => rule.offset(timestamp),
+            /* before the first transition */
+            (0, _) => self.offsets[0],
+            (idx, _) => self.offsets[self.transitions[idx - 1].1],
+        }
+    }
+}
+
+impl Rule {
+    /* std offset [dst [offset] [,start[/time],end[/time]]], offsets are west of Greenwich */
+    fn parse(s: &str) -> Option<Rule> {
+        let mut s = s.as_bytes();
+        skip_name(&mut s)?;
+        let std_offset = -parse_time(&mut s)?;
+        if s.is_empty() {
+            return Some(Rule {
+                std_offset,
+                dst: None,
+            });
+        }
+        skip_name(&mut s)?;
+        let offset = if s.first() != Some(&b',') {
+            -parse_time(&mut s)?
+        } else {
+            std_offset + 3600
+        };
+        let date = |s: &mut &[u8]| -> Option<(RuleDate, i64)> {
+            *s = s.strip_prefix(b",")?;
+            let date = parse_date(s)?;
+            let time = match s.strip_prefix(b"/") {
+                Some(rest) => {
+                    *s = rest;
+                    parse_time(s)?
+                }
+                None => 2 * 3600,
+            };
+            Some((date, time))
+        };
+        let start = date(&mut s)?;
+        let end = date(&mut s)?;
+        if !s.is_empty() {
+            return None;
+        }
+        Some(Rule {
+            std_offset,
+            dst: Some(Dst { offset, start, end }),
+        })
+    }
+
+    fn offset(&self, timestamp: i64) -> i64 {
+        let Some(dst) = self.dst else {
+            return self.std_offset;
+        };
+        let (year, _, _) = civil_from_days((timestamp + self.std_offset).div_euclid(86400));
+        /* the start is given in standard time and the end in daylight saving time */
+        let start = dst.start.0.days(year) * 86400 + dst.start.1 - self.std_offset;
+        let end = dst.end.0.days(year) * 86400 + dst.end.1 - dst.offset;
+        let in_dst = if start < end {
+            (start..end).contains(&timestamp)
+        } else {
+            /* southern hemisphere, daylight saving time over new year */
+            !(end..start).contains(&timestamp)
+        };
+        if in_dst {
+            dst.offset
+        } else {
+            self.std_offset
+        }
+    }
+}
+
+impl RuleDate {
+    /* Days since the UNIX epoch of this date in year */
+    fn days(self, year: i64) -> i64 {
+        let is_leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        match self {
+            RuleDate::Julian1(n) => {
+                days_from_civil(year, 1, 1) + n - 1 + i64::from(is_leap && n >= 60)
+            }
+            RuleDate::Julian0(n) => days_from_civil(year, 1, 1) + n,
+            RuleDate::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            } => {
+                let first = days_from_civil(year, month, 1);
+                let next_month = if month == 12 {
+                    days_from_civil(year + 1, 1, 1)
+                } else {
+                    days_from_civil(year, month + 1, 1)
+                };
+                /* 1970-01-01 was a Thursday */
+                let mut day = first + (weekday - (first + 4)).rem_euclid(7) + (week - 1) * 7;
+                while day >= next_month {
+                    day -= 7;
+                }
+                day
+            }
+        }
+    }
+}
+
+/* Skips a time zone abbreviation, either letters or quoted in <> */
+fn skip_name(s: &mut &[u8]) -> Option<()> {
+    let len = if s.first() == Some(&b'<') {
+        s.iter().position(|b| *b == b'>')? + 1
+    } else {
+        s.iter().take_while(|b| b.is_ascii_alphabetic()).count()
+    };
+    if len < 3 {
+        return None;
+    }
+    *s = &s[len..];
+    Some(())
+}
+
+fn parse_number(s: &mut &[u8]) -> Option<i64> {
+    let len = s.iter().take_while(|b| b.is_ascii_digit()).count();
+    let n = std::str::from_utf8(&s[..len]).ok()?.parse().ok()?;
+    *s = &s[len..];
+    Some(n)
+}
+
+/* [+-]hh[:mm[:ss]] in seconds */
+fn parse_time(s: &mut &[u8]) -> Option<i64> {
+    let sign = match s.first() {
+        Some(b'-') => -1,
+        _ => 1,
+    };
+    if matches!(s.first(), Some(b'+' | b'-')) {
+        *s = &s[1..];
+    }
+    let mut seconds = parse_number(s)? * 3600;
+    for unit in [60, 1] {
+        match s.strip_prefix(b":") {
+            Some(rest) => {
+                *s = rest;
+                seconds += parse_number(s)? * unit;
+            }
+            None => break,
+        }
+    }
+    Some(sign * seconds)
+}
+
+fn parse_date(s: &mut &[u8]) -> Option<RuleDate> {
+    match s.first()? {
+        b'J' => {
+            *s = &s[1..];
+            Some(RuleDate::Julian1(
+                parse_number(s).filter(|n| (1..=365).contains(n))?,
+            ))
+        }
+        b'M' => {
+            *s = &s[1..];
+            let month = parse_number(s).filter(|m| (1..=12).contains(m))?;
+            *s = s.strip_prefix(b".")?;
+            let week = parse_number(s).filter(|w| (1..=5).contains(w))?;
+            *s = s.strip_prefix(b".")?;
+            let weekday = parse_number(s).filter(|d| (0..=6).contains(d))?;
+            Some(RuleDate::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            })
+        }
+        _ => Some(RuleDate::Julian0(
+            parse_number(s).filter(|n| (0..=365).contains(n))?,
+        )),
+    }
+}
+
+/*
+ * Days since the UNIX epoch of a date of the proleptic Gregorian calendar, and back, from
+ * Howard Hinnant's date algorithms: <https://howardhinnant.github.io/date_algorithms.html>
+ */
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+pub(crate) fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[test]
+fn test_timezone() {
+    /* A version 2 TZif file with the given transitions, local time types and footer */
+    fn tzif(transitions: &[(i64, u8)], offsets: &[i32], footer: &str) -> Vec<u8> {
+        let header = |time_size: usize| {
+            let mut header = b"TZif2".to_vec();
+            header.extend([0; 15]);
+            for count in [0, 0, 0, transitions.len(), offsets.len(), 4] {
+                header.extend((count as u32).to_be_bytes());
+            }
+            let mut block = vec![];
+            for (time, _) in transitions {
+                if time_size == 8 {
+                    block.extend(time.to_be_bytes());
+                } else {
+                    block.extend((*time as i32).to_be_bytes());
+                }
+            }
+            block.extend(transitions.iter().map(|(_, index)| index));
+            for offset in offsets {
+                block.extend(offset.to_be_bytes());
+                block.extend([0, 0]);
+            }
+            block.extend(b"LMT\0");
+            header.extend(block);
+            header
+        };
+        let mut ret = header(4);
+        ret.extend(header(8));
+        ret.extend(format!("\n{}\n", footer).bytes());
+        ret
+    }
+
+    /* Europe/Athens: 2021-03-28 01:00 UTC to 2021-10-31 01:00 UTC is EEST */
+    let athens = TimeZone::parse(&tzif(&[], &[7200], "EET-2EEST,M3.5.0/3,M10.5.0/4")).unwrap();
+    assert_eq!(athens.offset(1616893199), 7200);
+    assert_eq!(athens.offset(1616893200), 3 * 3600);
+    assert_eq!(athens.offset(1635641999), 3 * 3600);
+    assert_eq!(athens.offset(1635642000), 7200);
+    /* Australia/Sydney: AEDT until 2021-04-03 16:00 UTC and from 2021-10-02 16:00 UTC */
+    let sydney = TimeZone::parse(&tzif(&[], &[36000], "AEST-10AEDT,M10.1.0,M4.1.0/3")).unwrap();
+    assert_eq!(sydney.offset(1609459200), 11 * 3600);
+    assert_eq!(sydney.offset(1617465600), 10 * 3600);
+    assert_eq!(sydney.offset(1633190400), 11 * 3600);
+    /* Asia/Kolkata: transitions, then no daylight saving time */
+    let kolkata = TimeZone::parse(&tzif(
+        &[(-891582800, 1), (-872058600, 0)],
+        &[19800, 23400],
+        "IST-5:30",
+    ))
+    .unwrap();
+    assert_eq!(kolkata.offset(-900000000), 19800);
+    assert_eq!(kolkata.offset(-880000000), 23400);
+    assert_eq!(kolkata.offset(1637831210), 19800);
+    assert_eq!(
+        TimeZone::parse(&tzif(&[], &[-10800], "<-03>3"))
+            .unwrap()
+            .offset(0),
+        -10800
+    );
+
+    assert_eq!(
+        TimeZone::from_posix("EET-2EEST,M3.5.0/3,M10.5.0/4"),
+        Some(athens)
+    );
+    assert!(TimeZone::from_posix("EET-2EEST,M3.5.0").is_none());
+
+    assert!(TimeZone::parse(b"TZif2").is_err());
+    assert!(TimeZone::parse(&tzif(&[(0, 3)], &[0], "UTC0")).is_err());
+    assert!(TimeZone::parse(&tzif(&[], &[0], "EET-2EEST,M3.5.0")).is_err());
+    assert!(TimeZone::load("../etc/passwd").is_err());
+
+    assert_eq!(civil_from_days(0), (1970, 1, 1));
+    assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    assert_eq!(days_from_civil(2000, 3, 1), 11017);
+    assert_eq!(
+        civil_from_days(days_from_civil(2100, 2, 28) + 1),
+        (2100, 3, 1)
+    );
+}
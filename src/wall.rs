@@ -0,0 +1,178 @@
+/*
+ * kitkat
+ *
+ * Copyright 2021 - Manos Pitsidianakis
+ *
+ * This file is part of kitkat.
+ *
+ * kitkat is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * kitkat is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with kitkat. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::*;
+
+/// Height of the caption under each clock of a [`ClockWall`], before scaling.
+pub const CAPTION_HEIGHT: usize = 11;
+
+/// A row of clocks, each showing its own time with a caption under it, e.g. one per office.
+pub struct ClockWall {
+    renderer: ClockRenderer,
+    captions: Vec<Image>,
+}
+
+impl ClockWall {
+    /// One clock drawn with `config` for each of `captions`, which can have letters, digits and
+    /// spaces.
+    pub fn new(config: Config, captions: &[&str]) -> Result<Self, String> {
+        let captions = captions
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                let mut caption = date::caption(text).ok_or_else(|| {
+                    format!(
+                        "caption `{}` can only have the letters A to Z, digits and spaces",
+                        text
+                    )
+                })?;
+                if caption.width > CAT_WIDTH {
+                    return Err(format!("caption `{}` is wider than a clock", text));
+                }
+                caption.x_offset = i * CAT_WIDTH + (CAT_WIDTH - caption.width) / 2;
+                caption.y_offset = CAT_HEIGHT + (CAPTION_HEIGHT - caption.height) / 2;
+                Ok(caption)
+            })
+            .collect::<Result<Vec<Image>, String>>()?;
+        Ok(ClockWall {
+            renderer: ClockRenderer::new(config),
+            captions,
+        })
+    }
+
+    pub fn config(&self) -> &Config {
+        self.renderer.config()
+    }
+
+    /// Draws the clocks and captions `scale` times larger.
+    pub fn set_scale(&mut self, scale: usize) {
+        self.renderer = ClockRenderer::new(Config {
+            scale,
+            ..*self.config()
+        });
+    }
+
+    /// Switches every clock to `theme`.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.renderer.set_theme(theme);
+    }
+
+    /// Number of clocks.
+    pub fn len(&self) -> usize {
+        self.captions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.captions.is_empty()
+    }
+
+    /// Width of the framebuffer.
+    pub fn width(&self) -> usize {
+        self.renderer.width() * self.len()
+    }
+
+    /// Height of the framebuffer.
+    pub fn height(&self) -> usize {
+        self.renderer.height() + CAPTION_HEIGHT * self.config().scale
+    }
+
+    /// Renders tail/eyes frame `frame` into a new framebuffer, with a time for each clock.
+    pub fn render(&self, times: &[ClockTime], frame: usize) -> Vec<u32> {
+        let mut buffer = vec![self.config().theme.background; self.width() * self.height()];
+        self.render_into(&mut buffer, times, frame);
+        buffer
+    }
+
+    /// Renders tail/eyes frame `frame` into `buffer`, overwriting it, with the clocks showing
+    /// `times` in the order of their captions. `buffer` must be `self.width() x self.height()`
+    /// pixels.
+    pub fn render_into(&self, buffer: &mut [u32], times: &[ClockTime], frame: usize) {
+        let (theme, scale) = (self.config().theme, self.config().scale);
+        let (clock_width, row_width) = (self.renderer.width(), self.width());
+        buffer.fill(theme.background);
+        for (i, time) in times.iter().take(self.len()).enumerate() {
+            let clock = self.renderer.render(time, frame);
+            for (y, row) in clock.chunks(clock_width).enumerate() {
+                buffer[y * row_width + i * clock_width..][..clock_width].copy_from_slice(row);
+            }
+        }
+        for caption in &self.captions {
+            caption.draw_scaled(buffer, row_width, scale, theme.date, None);
+        }
+    }
+}
+
+#[test]
+fn test_wall() {
+    let config = Config {
+        scale: 2,
+        ..Config::default()
+    };
+    let wall = ClockWall::new(config, &["Athens", "NYC 2"]).unwrap();
+    assert_eq!(wall.len(), 2);
+    assert_eq!(
+        (wall.width(), wall.height()),
+        (4 * CAT_WIDTH, 2 * (CAT_HEIGHT + CAPTION_HEIGHT))
+    );
+    let athens = ClockTime {
+        hour: 10,
+        minutes: 8,
+        seconds: 42,
+        nanos: 0,
+        mday: 25,
+        wday: 4,
+        timestamp: 1637831210,
+    };
+    let nyc = ClockTime { hour: 3, ..athens };
+    let buffer = wall.render(&[athens, nyc], 0);
+
+    /* each clock as drawn alone, side by side */
+    let renderer = ClockRenderer::new(config);
+    let (width, row_width) = (renderer.width(), wall.width());
+    for (i, time) in [athens, nyc].iter().enumerate() {
+        for (y, row) in renderer.render(time, 0).chunks(width).enumerate() {
+            assert_eq!(&buffer[y * row_width + i * width..][..width], row);
+        }
+    }
+    /* and the captions under them */
+    let captions = &buffer[renderer.height() * row_width..];
+    for i in 0..2 {
+        assert!(captions
+            .chunks(row_width)
+            .any(|row| row[i * width..(i + 1) * width].contains(&BLACK)));
+    }
+    assert!(captions
+        .chunks(row_width)
+        .all(|row| row[..16] == [WHITE; 16]));
+
+    /* rescaled, it is the same as built at that scale */
+    let mut wall = wall;
+    wall.set_scale(1);
+    assert_eq!(
+        wall.render(&[athens, nyc], 0),
+        ClockWall::new(Config::default(), &["Athens", "NYC 2"])
+            .unwrap()
+            .render(&[athens, nyc], 0)
+    );
+
+    assert!(ClockWall::new(config, &["Zürich"]).is_err());
+    assert!(ClockWall::new(config, &["Llanfairpwllgwyngyll station"]).is_err());
+}